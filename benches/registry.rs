@@ -0,0 +1,118 @@
+use std::sync::mpsc;
+
+use criterion::{criterion_group, criterion_main, Criterion, BatchSize, black_box};
+
+use pochta::{dense_registry, linear_registry, registry, Registry, Channel, DenseRegistry, DenseChannel, LinearRegistry, LinearChannel};
+
+const KEYS: u32 = 1024;
+//Number of subscribers, for which linear search is still viable
+const SMALL_KEYS: u32 = 16;
+
+type Reg = (Channel<u32, u64, mpsc::Sender<u64>>, Registry<u32, u64, mpsc::Sender<u64>>);
+
+fn setup(subscribers: u32) -> (Reg, mpsc::Receiver<u64>) {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = registry();
+    for key in 0..subscribers {
+        channel.subscribe(key, send.clone()).expect("Subscribe");
+    }
+    ((channel, registry), recv)
+}
+
+type DenseReg = (DenseChannel<u64, mpsc::Sender<u64>>, DenseRegistry<u64, mpsc::Sender<u64>>);
+
+fn setup_dense(subscribers: u32) -> (DenseReg, mpsc::Receiver<u64>) {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = dense_registry(KEYS as usize - 1);
+    for key in 0..subscribers {
+        channel.subscribe(key as usize, send.clone()).expect("Subscribe");
+    }
+    ((channel, registry), recv)
+}
+
+type LinearReg = (LinearChannel<u32, u64, mpsc::Sender<u64>>, LinearRegistry<u32, u64, mpsc::Sender<u64>>);
+
+fn setup_linear(subscribers: u32) -> (LinearReg, mpsc::Receiver<u64>) {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = linear_registry();
+    for key in 0..subscribers {
+        channel.subscribe(key, send.clone()).expect("Subscribe");
+    }
+    ((channel, registry), recv)
+}
+
+//Processes all queued messages by finishing registry
+fn drain(registry: Registry<u32, u64, mpsc::Sender<u64>>) {
+    black_box(registry.finish());
+}
+
+fn subscribe(c: &mut Criterion) {
+    c.bench_function("subscribe", |b| b.iter_batched(|| setup(0), |((channel, registry), _recv)| {
+        let (send, _recv) = mpsc::channel();
+        for key in 0..KEYS {
+            channel.subscribe(key, send.clone()).expect("Subscribe");
+        }
+        drain(registry);
+    }, BatchSize::SmallInput));
+
+    c.bench_function("subscribe_overwrite", |b| b.iter_batched(|| setup(KEYS), |((channel, registry), _recv)| {
+        let (send, _recv) = mpsc::channel();
+        for key in 0..KEYS {
+            channel.subscribe(key, send.clone()).expect("Subscribe");
+        }
+        drain(registry);
+    }, BatchSize::SmallInput));
+}
+
+fn send(c: &mut Criterion) {
+    c.bench_function("send_to", |b| b.iter_batched(|| setup(KEYS), |((channel, registry), recv)| {
+        for key in 0..KEYS {
+            channel.send_to(key, key as u64).expect("Send");
+        }
+        drain(registry);
+        black_box(recv.try_iter().count());
+    }, BatchSize::SmallInput));
+
+    c.bench_function("send_to_dense", |b| b.iter_batched(|| setup_dense(KEYS), |((channel, registry), recv)| {
+        for key in 0..KEYS {
+            channel.send_to(key as usize, key as u64).expect("Send");
+        }
+        black_box(registry.finish());
+        black_box(recv.try_iter().count());
+    }, BatchSize::SmallInput));
+
+    c.bench_function("send_to_small", |b| b.iter_batched(|| setup(SMALL_KEYS), |((channel, registry), recv)| {
+        for key in 0..KEYS {
+            channel.send_to(key % SMALL_KEYS, key as u64).expect("Send");
+        }
+        drain(registry);
+        black_box(recv.try_iter().count());
+    }, BatchSize::SmallInput));
+
+    c.bench_function("send_to_small_linear", |b| b.iter_batched(|| setup_linear(SMALL_KEYS), |((channel, registry), recv)| {
+        for key in 0..KEYS {
+            channel.send_to(key % SMALL_KEYS, key as u64).expect("Send");
+        }
+        black_box(registry.finish());
+        black_box(recv.try_iter().count());
+    }, BatchSize::SmallInput));
+
+    c.bench_function("send_to_vacant", |b| b.iter_batched(|| setup(0), |((channel, registry), _recv)| {
+        for key in 0..KEYS {
+            channel.send_to(key, key as u64).expect("Send");
+        }
+        drain(registry);
+    }, BatchSize::SmallInput));
+}
+
+fn unsubscribe(c: &mut Criterion) {
+    c.bench_function("unsubscribe", |b| b.iter_batched(|| setup(KEYS), |((channel, registry), _recv)| {
+        for key in 0..KEYS {
+            channel.unsubscribe(key).expect("Unsubscribe");
+        }
+        drain(registry);
+    }, BatchSize::SmallInput));
+}
+
+criterion_group!(benches, subscribe, send, unsubscribe);
+criterion_main!(benches);
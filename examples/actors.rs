@@ -0,0 +1,45 @@
+use std::sync::mpsc;
+
+use pochta::registry;
+
+#[derive(Debug)]
+enum Command {
+    Add(u64),
+    Report(mpsc::Sender<u64>),
+}
+
+fn main() {
+    let (channel, mut registry) = registry::<&'static str, Command, mpsc::Sender<Command>>();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    let mut actors = Vec::new();
+    for name in ["alice", "bob"] {
+        let mut total = 0;
+        let actor = channel.spawn_actor(name, move |command| match command {
+            Command::Add(value) => total += value,
+            Command::Report(reply) => {
+                let _ = reply.send(total);
+            },
+        }).expect("Registry to run");
+        actors.push(actor);
+    }
+
+    for value in 1..=10 {
+        channel.send_to("alice", Command::Add(value)).expect("Registry to run");
+        channel.send_to("bob", Command::Add(value * 2)).expect("Registry to run");
+    }
+
+    let (reply, totals) = mpsc::channel();
+    for actor in actors.iter() {
+        actor.send(Command::Report(reply.clone())).expect("Registry to run");
+        println!("{}: total={}", actor.key(), totals.recv().expect("Actor to reply"));
+    }
+
+    for actor in actors {
+        actor.stop().expect("Actor to finish");
+    }
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
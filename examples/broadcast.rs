@@ -0,0 +1,43 @@
+use std::sync::{mpsc, Arc};
+
+use pochta::shared_registry;
+
+const TOPIC: &str = "frames";
+const SUBSCRIBERS: usize = 8;
+
+///Large payload, which is too costly to clone per subscriber
+struct Frame {
+    id: usize,
+    pixels: Vec<u8>,
+}
+
+fn main() {
+    let (channel, mut registry) = shared_registry::<&'static str, Frame, mpsc::Sender<Arc<Frame>>>();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    let mut receivers = Vec::with_capacity(SUBSCRIBERS);
+    for _ in 0..SUBSCRIBERS {
+        let (send, recv) = mpsc::channel();
+        channel.subscribe_broadcast(TOPIC, send).expect("Registry to run");
+        receivers.push(recv);
+    }
+
+    for id in 0..3 {
+        let frame = Frame {
+            id,
+            pixels: vec![0; 1920 * 1080 * 4],
+        };
+        channel.broadcast_shared_to(TOPIC, frame).expect("Registry to run");
+    }
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    for (idx, recv) in receivers.iter().enumerate() {
+        for frame in recv.iter() {
+            println!("subscriber={} frame={} size={} refs={}", idx, frame.id, frame.pixels.len(), Arc::strong_count(&frame));
+        }
+    }
+}
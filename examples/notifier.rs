@@ -0,0 +1,49 @@
+//!Drives registry from event loop, which is notified via file descriptor.
+//!
+//!Reactor would watch read end with `epoll`/`io_uring` (e.g. `eventfd`), while this example
+//!simply blocks on reading it.
+
+#[cfg(unix)]
+fn main() {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::sync::mpsc;
+
+    let (notify_fd, mut events) = UnixStream::pair().expect("Create socket pair");
+    notify_fd.set_nonblocking(true).expect("Set non-blocking");
+    let waker = pochta::notify_waker(move || {
+        //Pending byte is already enough to wake reactor, so full buffer is fine
+        let _ = (&notify_fd).write(&[1]);
+    });
+
+    let (channel, mut registry) = pochta::registry::<u8, &'static str, mpsc::Sender<&'static str>>();
+    let reactor = std::thread::spawn(move || {
+        let mut buffer = [0u8; 64];
+        let mut notifications = 0;
+        loop {
+            match registry.poll_with(&waker) {
+                core::task::Poll::Ready(reason) => break (reason, notifications),
+                core::task::Poll::Pending => {
+                    //Wait for readiness of fd, draining pending notifications
+                    notifications += events.read(&mut buffer).expect("Read notification");
+                },
+            }
+        }
+    });
+
+    let (send, recv) = mpsc::channel();
+    channel.subscribe(1, send).expect("Registry to run");
+    for msg in ["first", "second", "third"] {
+        channel.send_to(1, msg).expect("Registry to run");
+        println!("received: {}", recv.recv().expect("Delivered"));
+    }
+
+    drop(channel);
+    let (reason, notifications) = reactor.join().expect("Finish successfully");
+    println!("registry finished after {} notifications: {}", notifications, reason);
+}
+
+#[cfg(not(unix))]
+fn main() {
+    println!("This example requires unix");
+}
@@ -0,0 +1,56 @@
+//!Runs registry as `smol` task, delivering messages to async subscribers.
+
+#[cfg(not(loom))]
+use pochta::{SendError, SendErrorKind};
+
+#[cfg(not(loom))]
+///Subscriber, delivering messages to async channel
+struct AsyncSender<T>(smol::channel::Sender<T>);
+
+#[cfg(not(loom))]
+impl<T: Send> pochta::Sender<T> for AsyncSender<T> {
+    #[inline]
+    fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        //Channel is unbounded, so it can only fail due to being closed
+        self.0.try_send(value).map_err(|error| SendError {
+            kind: SendErrorKind::Closed,
+            message: error.into_inner(),
+        })
+    }
+
+    #[inline]
+    fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+}
+
+#[cfg(not(loom))]
+fn main() {
+    smol::block_on(async {
+        let (channel, registry) = pochta::registry::<&'static str, String, AsyncSender<String>>();
+        //Registry is woken by channels via task's waker, so it needs no thread of its own
+        let registry = smol::spawn(registry);
+
+        let (send, recv) = smol::channel::unbounded();
+        channel.subscribe("greetings", AsyncSender(send)).expect("Registry to run");
+        let consumer = smol::spawn(async move {
+            while let Ok(msg) = recv.recv().await {
+                println!("received: {}", msg);
+            }
+        });
+
+        for name in ["smol", "pochta"] {
+            channel.send_to("greetings", format!("hello, {}", name)).expect("Registry to run");
+        }
+        channel.unsubscribe("greetings").expect("Registry to run");
+
+        drop(channel);
+        println!("registry finished: {}", registry.await);
+        consumer.await;
+    });
+}
+
+#[cfg(loom)]
+fn main() {
+    println!("This example requires smol, which does not support loom");
+}
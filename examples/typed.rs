@@ -0,0 +1,45 @@
+use std::sync::mpsc;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Room {
+    Lobby,
+    Games,
+}
+
+pochta::define_registry! {
+    ///Channel of chat rooms
+    //Generates:
+    //- `Rooms::new() -> (Rooms, Registry<Room, String, mpsc::Sender<String>>)`
+    //- `Rooms::subscribe(&self, Room, mpsc::Sender<String>)`
+    //- `Rooms::unsubscribe(&self, Room)`
+    //- `Rooms::send_to(&self, Room, String)`
+    //- `Rooms::into_inner(self) -> Channel<Room, String, mpsc::Sender<String>>`
+    //- `Deref<Target = Channel<Room, String, mpsc::Sender<String>>>` for the rest
+    struct Rooms {
+        key: Room,
+        message: String,
+        sender: mpsc::Sender<String>,
+        builder: |builder| builder.capacity(2),
+    }
+}
+
+fn main() {
+    let (rooms, mut registry) = Rooms::new();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    let (lobby, lobby_recv) = mpsc::channel();
+    let (games, games_recv) = mpsc::channel();
+    rooms.subscribe(Room::Lobby, lobby).expect("Registry to run");
+    rooms.subscribe(Room::Games, games).expect("Registry to run");
+
+    rooms.send_to(Room::Lobby, "welcome".to_owned()).expect("Registry to run");
+    rooms.send_to(Room::Games, "next round".to_owned()).expect("Registry to run");
+    println!("lobby: {}", lobby_recv.recv().expect("Delivered"));
+    println!("games: {}", games_recv.recv().expect("Delivered"));
+    println!("pending: {}", rooms.pending());
+
+    drop(rooms);
+    worker.join().expect("Finish successfully");
+}
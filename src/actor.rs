@@ -0,0 +1,63 @@
+use core::hash::Hash;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::{BoundSender, Channel, Cancelled};
+
+///Actor, handling messages of a single key on its own thread.
+///
+///Dropping it does not stop actor, which keeps running as long as it is subscribed.
+pub struct Actor<K: PartialEq + Eq + Hash + Clone, T: Send> {
+    sender: BoundSender<K, T, mpsc::Sender<T>>,
+    worker: thread::JoinHandle<()>,
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send> Actor<K, T> {
+    #[inline(always)]
+    ///Returns key of the actor
+    pub fn key(&self) -> &K {
+        self.sender.key()
+    }
+
+    #[inline(always)]
+    ///Sends message `msg` to the actor via registry.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send(&self, msg: T) -> Result<(), Cancelled> {
+        self.sender.send(msg)
+    }
+
+    ///Stops actor, by unsubscribing it, and waits for its thread to finish.
+    ///
+    ///Actor handles messages, that are already delivered, before finishing.
+    ///If registry is not running, actor finishes once registry is dropped.
+    ///
+    ///Returns `Err` if handler panicked.
+    pub fn stop(self) -> thread::Result<()> {
+        let _ = self.sender.unsubscribe();
+        self.worker.join()
+    }
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send + 'static> Channel<K, T, mpsc::Sender<T>> {
+    ///Spawns thread, running `handler` for every message of `key`, and subscribes it.
+    ///
+    ///Returns `Ok(Actor)` if registry is still running
+    ///Returns `Err(Cancelled)` if actor cannot be subscribed due to registry not running
+    pub fn spawn_actor<F: FnMut(T) + Send + 'static>(&self, key: K, mut handler: F) -> Result<Actor<K, T>, Cancelled> {
+        let (send, recv) = mpsc::channel();
+        self.subscribe(key.clone(), send)?;
+
+        let worker = thread::spawn(move || {
+            for msg in recv.iter() {
+                handler(msg);
+            }
+        });
+
+        Ok(Actor {
+            sender: self.bind(key),
+            worker,
+        })
+    }
+}
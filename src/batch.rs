@@ -0,0 +1,251 @@
+use core::{fmt, mem, task, time};
+use core::future::Future;
+use core::hash::Hash;
+use core::pin::Pin;
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::driver::{self, Driver};
+use crate::{deliver, is_alive, Cancelled, Sender, State};
+
+pub(crate) enum BatchMessage<K, T: Send, S: Sender<Vec<T>>> {
+    Subscribe(K, S),
+    Unsubscribe(K),
+    Msg(K, T),
+}
+
+///Channel of `BatchRegistry`.
+pub struct BatchChannel<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>> {
+    channel: driver::Tx<BatchMessage<K, T, S>>,
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>> BatchChannel<K, T, S> {
+    #[inline(always)]
+    ///Subscribes provided `channel` with specified `key`, potentially removing existing channel
+    ///
+    ///Pending batch of removed channel is flushed to it first.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe(&self, key: K, channel: S) -> Result<(), Cancelled> {
+        self.channel.send(BatchMessage::Subscribe(key, channel))
+    }
+
+    #[inline(always)]
+    ///Removes `channel` with specified `key` from registry, flushing its pending batch
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn unsubscribe(&self, key: K) -> Result<(), Cancelled> {
+        self.channel.send(BatchMessage::Unsubscribe(key))
+    }
+
+    #[inline(always)]
+    ///Adds message `msg` to batch of channel with specified `key`
+    ///
+    ///Message to key without subscriber is dropped.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to(&self, key: K, msg: T) -> Result<(), Cancelled> {
+        self.channel.send(BatchMessage::Msg(key, msg))
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>> Clone for BatchChannel<K, T, S> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>> fmt::Debug for BatchChannel<K, T, S> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("BatchChannel")
+           .field("id", &self.channel.state.id)
+           .finish()
+    }
+}
+
+struct Batch<T, S> {
+    channel: S,
+    pending: Vec<T>,
+    //Moment first message of pending batch arrived
+    since: Instant,
+}
+
+impl<T, S: Sender<Vec<T>>> Batch<T, S> {
+    #[inline(always)]
+    fn new(channel: S) -> Self {
+        Self {
+            channel,
+            pending: Vec::new(),
+            since: Instant::now(),
+        }
+    }
+
+    //Returns moment batch is due, `None` if it is too far to be ever reached
+    #[inline(always)]
+    fn due(&self, max_delay: time::Duration) -> Option<Instant> {
+        self.since.checked_add(max_delay)
+    }
+
+    //Returns whether subscriber is still alive
+    fn flush(&mut self, state: &State) -> bool {
+        if self.pending.is_empty() {
+            return true;
+        }
+
+        let batch = mem::take(&mut self.pending);
+        let len = batch.len();
+        let result = deliver(&self.channel, batch);
+        if result.is_ok() {
+            state.on_delivered(len);
+        }
+        is_alive(result)
+    }
+}
+
+///Registry of subscribers, accepting messages in batches.
+///
+///Messages to each key are accumulated and delivered as single `Vec<T>` once batch reaches
+///`max_batch` messages or `max_delay` elapses since first message of batch arrived, whichever
+///comes first. Bulk consumers pay per-message overhead of channel once per batch, which greatly
+///improves throughput, at cost of latency: message may wait up to `max_delay` before it is delivered.
+///
+///Pending batch is flushed to subscriber, when it is unsubscribed or replaced, and to all
+///subscribers once registry is disconnected or finished, so no accepted message is lost.
+///Batch, that is rejected by subscriber, is dropped as whole.
+///
+///Delay is tracked by `run`, which parks thread until the earliest batch is due.
+///When polled as `Future`, registry has no timer, so due batches are flushed on next poll only.
+pub struct BatchRegistry<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>> {
+    registry: HashMap<K, Batch<T, S>>,
+    recv: driver::Rx<BatchMessage<K, T, S>>,
+    max_batch: usize,
+    max_delay: time::Duration,
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>> BatchRegistry<K, T, S> {
+    ///Process registry messages until cancelled.
+    ///
+    ///This function blocks, until all sending channels gets closed
+    pub fn run(&mut self) -> Cancelled {
+        self.run_queue()
+    }
+
+    ///Processes all queued messages and flushes pending batches, returning subscribers.
+    pub fn finish(mut self) -> HashMap<K, S> {
+        while let Ok(message) = self.recv.try_recv() {
+            self.handle(message);
+        }
+        self.flush_all();
+        mem::take(&mut self.registry).into_iter().map(|(key, batch)| (key, batch.channel)).collect()
+    }
+
+    #[inline(always)]
+    fn next_due(&self) -> Option<Instant> {
+        self.registry.values().filter(|batch| !batch.pending.is_empty()).filter_map(|batch| batch.due(self.max_delay)).min()
+    }
+
+    fn flush_due(&mut self) {
+        let now = Instant::now();
+        let max_delay = self.max_delay;
+        let state = self.recv.state();
+        self.registry.retain(|_, batch| batch.pending.is_empty() || batch.due(max_delay).map_or(true, |due| now < due) || batch.flush(state));
+    }
+
+    fn flush_all(&mut self) {
+        let state = self.recv.state();
+        self.registry.retain(|_, batch| batch.flush(state));
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>> Driver for BatchRegistry<K, T, S> {
+    type Message = BatchMessage<K, T, S>;
+
+    #[inline(always)]
+    fn rx(&self) -> &driver::Rx<Self::Message> {
+        &self.recv
+    }
+
+    #[inline(always)]
+    fn drained(&mut self) {
+        self.flush_due();
+    }
+
+    #[inline(always)]
+    fn disconnected(&mut self) {
+        self.flush_all();
+    }
+
+    //Wakes up in time for the earliest due batch
+    fn park(&self) {
+        match self.next_due() {
+            Some(due) => std::thread::park_timeout(due.saturating_duration_since(Instant::now())),
+            None => std::thread::park(),
+        }
+    }
+
+    fn handle(&mut self, message: BatchMessage<K, T, S>) {
+        match message {
+            BatchMessage::Subscribe(key, channel) => {
+                if let Some(mut old) = self.registry.insert(key, Batch::new(channel)) {
+                    old.flush(self.recv.state());
+                }
+            },
+            BatchMessage::Unsubscribe(key) => if let Some(mut old) = self.registry.remove(&key) {
+                old.flush(self.recv.state());
+            },
+            BatchMessage::Msg(key, message) => if let Some(batch) = self.registry.get_mut(&key) {
+                if batch.pending.is_empty() {
+                    batch.since = Instant::now();
+                }
+                batch.pending.push(message);
+                if batch.pending.len() >= self.max_batch && !batch.flush(self.recv.state()) {
+                    self.registry.remove(&key);
+                }
+            },
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>> fmt::Debug for BatchRegistry<K, T, S> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("BatchRegistry")
+           .field("subscribers", &self.registry.len())
+           .field("max_batch", &self.max_batch)
+           .field("max_delay", &self.max_delay)
+           .finish()
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>> Unpin for BatchRegistry<K, T, S> {}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>> Future for BatchRegistry<K, T, S> {
+    type Output = Cancelled;
+
+    #[inline(always)]
+    fn poll(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        self.get_mut().poll_queue(ctx.waker())
+    }
+}
+
+///Creates new registry, delivering messages in batches of up to `max_batch` messages, delayed by at most `max_delay`.
+///
+///`max_batch` of 0 is treated as 1. `max_delay`, that is too large to be represented (e.g. `Duration::MAX`),
+///never elapses, so batches are delivered only once full. Refer to `BatchRegistry` for details.
+pub fn batching_registry<K: PartialEq + Eq + Hash, T: Send, S: Sender<Vec<T>>>(max_batch: usize, max_delay: time::Duration) -> (BatchChannel<K, T, S>, BatchRegistry<K, T, S>) {
+    let (channel, recv) = driver::channel();
+
+    (BatchChannel {
+        channel,
+    }, BatchRegistry {
+        registry: HashMap::new(),
+        recv,
+        max_batch: core::cmp::max(max_batch, 1),
+        max_delay,
+    })
+}
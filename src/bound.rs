@@ -0,0 +1,58 @@
+use core::hash::Hash;
+
+use crate::{Channel, Cancelled, Sender};
+
+///Sender bound to a single key of registry
+///
+///Dropping it does not unsubscribe key.
+pub struct BoundSender<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M = ()> {
+    key: K,
+    channel: Channel<K, T, S, M>,
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M> BoundSender<K, T, S, M> {
+    #[inline(always)]
+    ///Returns key, this sender is bound to
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    #[inline(always)]
+    ///Sends message `msg` over to channel registered by bound key.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send(&self, msg: T) -> Result<(), Cancelled> {
+        self.channel.send_to(self.key.clone(), msg)
+    }
+
+    #[inline(always)]
+    ///Removes channel with bound key from registry, consuming sender.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn unsubscribe(self) -> Result<(), Cancelled> {
+        self.channel.unsubscribe(self.key)
+    }
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M> Channel<K, T, S, M> {
+    #[inline(always)]
+    ///Creates sender bound to the `key`, avoiding need to specify key on every send.
+    pub fn bind(&self, key: K) -> BoundSender<K, T, S, M> {
+        BoundSender {
+            key,
+            channel: self.clone(),
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M> Clone for BoundSender<K, T, S, M> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            channel: self.channel.clone(),
+        }
+    }
+}
@@ -0,0 +1,511 @@
+use core::fmt;
+use core::hash::Hash;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::sync::atomic::Ordering;
+use std::sync::{mpsc, Arc};
+use std::collections::HashMap;
+
+use crate::{queue, Metrics, Envelope, Transport, Channel, Registry, RegistryEvent, Sender, State, Subscriber, EvictHook, EmptyHook, EventHook, CheckpointHook, Checkpoint, Hooks, Inflight, Lifo, Middleware, Priority, SubscriberFactory};
+
+///Registry builder, allowing to configure optional features.
+pub struct RegistryBuilder<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M = ()> {
+    capacity: usize,
+    subscribers: HashMap<K, S>,
+    dead_letter: Option<S>,
+    on_evict: Option<EvictHook<K>>,
+    events: Option<EventHook<K>>,
+    on_empty: Option<EmptyHook>,
+    on_nonempty: Option<EmptyHook>,
+    middleware: Option<Middleware<K, T>>,
+    factory: Option<(SubscriberFactory<K, S>, fn(&K) -> K)>,
+    checkpoint: Option<(core::time::Duration, CheckpointHook<K>)>,
+    poll_budget: usize,
+    max_subscribers: usize,
+    max_keys_per_sender: usize,
+    drop_oldest: Option<usize>,
+    priority_control: bool,
+    coalesce: bool,
+    //Whether LIFO is enabled and whether it keeps only latest message per key
+    lifo: Option<bool>,
+    prioritize: bool,
+    inflight: Option<(usize, fn(&K) -> K)>,
+    transport: Option<(queue::Tx<K, T, S, M>, queue::Rx<K, T, S, M>)>,
+    metrics: Option<Metrics>,
+    #[cfg(feature = "log")]
+    key_fmt: Option<crate::KeyFmt<K>>,
+    #[cfg(feature = "tracing")]
+    trace_key: Option<crate::KeyFmt<K>>,
+    _msg: PhantomData<fn() -> T>,
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>> RegistryBuilder<K, T, S> {
+    #[inline(always)]
+    ///Creates new builder with default configuration
+    pub fn new() -> Self {
+        Self::with_meta()
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> RegistryBuilder<K, T, S, M> {
+    #[inline(always)]
+    ///Creates new builder with default configuration, for registry storing metadata `M` of subscribers.
+    ///
+    ///Refer to `Channel::subscribe_with_meta` for details.
+    pub fn with_meta() -> Self {
+        Self {
+            capacity: 0,
+            subscribers: HashMap::new(),
+            dead_letter: None,
+            on_evict: None,
+            events: None,
+            on_empty: None,
+            on_nonempty: None,
+            middleware: None,
+            factory: None,
+            checkpoint: None,
+            poll_budget: usize::MAX,
+            max_subscribers: usize::MAX,
+            max_keys_per_sender: usize::MAX,
+            drop_oldest: None,
+            priority_control: false,
+            coalesce: false,
+            lifo: None,
+            prioritize: false,
+            inflight: None,
+            transport: None,
+            metrics: None,
+            #[cfg(feature = "log")]
+            key_fmt: None,
+            #[cfg(feature = "tracing")]
+            trace_key: None,
+            _msg: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    ///Sets initial number of subscribers, registry can hold without re-allocation.
+    ///
+    ///Defaults to `0`
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets subscribers to be installed on start, replacing previously set ones.
+    pub fn subscribers(mut self, subscribers: HashMap<K, S>) -> Self {
+        self.subscribers = subscribers;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets `sink` to receive messages, that cannot be delivered via `send_to`.
+    ///
+    ///This includes messages to key without subscriber and messages rejected by closed subscriber.
+    ///Sink is removed once it is closed.
+    pub fn dead_letter(mut self, sink: S) -> Self {
+        self.dead_letter = Some(sink);
+        self
+    }
+
+    #[inline(always)]
+    ///Sets `hook` to be invoked with key of every subscriber, removed due to being closed.
+    ///
+    ///Hook runs on registry's thread, so it should be quick.
+    pub fn on_evict<F: FnMut(&K) + Send + 'static>(mut self, hook: F) -> Self {
+        self.on_evict = Some(Box::new(hook));
+        self
+    }
+
+    #[inline(always)]
+    ///Sets `hook` to be invoked whenever registry loses its last keyed subscriber.
+    ///
+    ///This allows to start idle timer (e.g. to shutdown worker) once nothing is subscribed.
+    ///Broadcast groups, round-robin groups and shards are not counted as subscribers. Hook is
+    ///invoked after operation, that removed last subscriber, on registry's thread, so it should
+    ///be quick. Unlike `on_evict`, removal via `unsubscribe` counts too.
+    pub fn on_empty<F: FnMut() + Send + 'static>(mut self, hook: F) -> Self {
+        self.on_empty = Some(Box::new(hook));
+        self
+    }
+
+    #[inline(always)]
+    ///Sets `hook` to be invoked whenever empty registry gets keyed subscriber.
+    ///
+    ///Refer to `on_empty` for details.
+    pub fn on_nonempty<F: FnMut() + Send + 'static>(mut self, hook: F) -> Self {
+        self.on_nonempty = Some(Box::new(hook));
+        self
+    }
+
+    #[inline(always)]
+    ///Sets `middleware` to transform every message right before delivery to keyed subscriber.
+    ///
+    ///Middleware is given key and message, returning message to deliver or `None` to drop it,
+    ///which allows to filter or enrich messages (e.g. with timestamp) without wrapping subscribers.
+    ///Dropped message is consumed by middleware, hence it is not passed to dead letter sink or
+    ///fallback key, and `send_to_confirm_blocking` reports it as not delivered.
+    ///Middleware is not invoked for messages to key without subscriber, broadcast groups,
+    ///round-robin groups and shards.
+    ///
+    ///Catch-all subscriber of `Channel::subscribe_all` receives copy of transformed message.
+    ///Middleware runs on registry's thread, so it should be quick.
+    pub fn middleware<F: FnMut(&K, T) -> Option<T> + Send + 'static>(mut self, middleware: F) -> Self {
+        self.middleware = Some(Box::new(middleware));
+        self
+    }
+
+    #[inline(always)]
+    ///Sets `hook` to be invoked with keys of all subscribers every `interval`.
+    ///
+    ///This allows to persist set of keys, so that application can re-subscribe them on recovery.
+    ///Broadcast groups and shards are not included.
+    ///
+    ///Hook runs on registry's thread, between handling batches of messages, so it should be quick
+    ///and interval is not precise: `Registry::run` wakes up for checkpoint on its own, but under
+    ///load it is delayed until queue is drained, or `poll_budget` is exhausted. When polled as
+    ///`Future`, checkpoint happens only on polls, as registry has no timer to wake it up.
+    ///Interval, that is too large to be represented (e.g. `Duration::MAX`), never elapses.
+    pub fn checkpoint<F: FnMut(&[&K]) + Send + 'static>(mut self, interval: core::time::Duration, hook: F) -> Self {
+        self.checkpoint = Some((interval, Box::new(hook)));
+        self
+    }
+
+    #[inline(always)]
+    ///Sets maximum number of messages to be processed at once, before yielding.
+    ///
+    ///When driven as `Future`, registry returns `Pending` after processing `budget` messages,
+    ///waking itself to be polled again, so that it doesn't monopolize executor under load.
+    ///
+    ///Zero is treated as `1`. Defaults to processing all queued messages.
+    pub fn poll_budget(mut self, budget: usize) -> Self {
+        self.poll_budget = core::cmp::max(budget, 1);
+        self
+    }
+
+    #[inline(always)]
+    ///Sets maximum number of keys with subscriber.
+    ///
+    ///Once limit is reached, subscription of new key is rejected, which can be observed via
+    ///`Channel::try_subscribe`, while existing key can still be re-subscribed.
+    ///Broadcast groups and `subscribers` set on start are not limited.
+    ///
+    ///Defaults to no limit.
+    pub fn max_subscribers(mut self, max: usize) -> Self {
+        self.max_subscribers = max;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets maximum number of keys, that can be subscribed on behalf of the same `SubscriberId`.
+    ///
+    ///Once limit is reached, subscription of new key via `subscribe_as` is rejected, which can be
+    ///observed via `Channel::try_subscribe_as`. This guards shared registry against client
+    ///bloating it with subscriptions. Checking limit requires registry to go over all
+    ///subscriptions, same as `unsubscribe_sender`. Channels, subscribed without id, are not limited.
+    ///
+    ///Defaults to no limit.
+    pub fn max_keys_per_sender(mut self, max: usize) -> Self {
+        self.max_keys_per_sender = max;
+        self
+    }
+
+    #[inline(always)]
+    ///Limits queue of registry to `capacity` messages, discarding oldest message on overflow.
+    ///
+    ///This is useful when freshness of messages is more important than their delivery.
+    ///Only messages, sent via `send_to`, are discarded, while other operations (e.g. `subscribe`)
+    ///are always queued, even if it exceeds `capacity`. Discarded messages are counted by
+    ///`Channel::overflowed_count` and passed to dead letter sink, if any, which keeps latest
+    ///`capacity` of them until registry gets to handle them. Older ones are lost, which is counted
+    ///by `Channel::overflow_lost_count`.
+    ///
+    ///Zero is treated as `1`. Defaults to unbounded queue.
+    pub fn drop_oldest(mut self, capacity: usize) -> Self {
+        self.drop_oldest = Some(core::cmp::max(capacity, 1));
+        self
+    }
+
+    #[inline(always)]
+    ///Limits queue of registry to `capacity` messages.
+    ///
+    ///This is the same as `drop_oldest`, as registry's queue never blocks sender: overflow is
+    ///handled by discarding oldest message, while `Channel::push` allows to reject message instead.
+    pub fn bounded(self, capacity: usize) -> Self {
+        self.drop_oldest(capacity)
+    }
+
+    #[inline(always)]
+    ///Attaches `handle` to counters of registry, once it is built, replacing previously set one.
+    ///
+    ///Refer to `Metrics` for details.
+    pub fn metrics(mut self, handle: Metrics) -> Self {
+        self.metrics = Some(handle);
+        self
+    }
+
+    #[inline(always)]
+    ///Enables separate queue for control operations, which are always handled before messages.
+    ///
+    ///This allows urgent operations (e.g. `unsubscribe`) to not wait behind flood of messages, but
+    ///changes ordering guarantee: control operation can take effect before messages, that are sent
+    ///prior to it. Order within each kind of operation is preserved. Messages include every
+    ///variation of `send_to` and `broadcast_to`.
+    pub fn priority_control(mut self) -> Self {
+        self.priority_control = true;
+        self
+    }
+
+    #[inline(always)]
+    ///Enables coalescing of messages sent via `send_to`.
+    ///
+    ///Registry drains its queue, keeping only the latest message per key, and delivers them once
+    ///queue is empty (or `poll_budget` is exhausted), which is useful for state updates where only
+    ///latest value matters.
+    ///
+    ///Messages are delivered in no particular order across keys, but any other operation (e.g.
+    ///`subscribe`) takes effect only after all previously sent messages are delivered.
+    pub fn coalesce(mut self) -> Self {
+        self.coalesce = true;
+        self
+    }
+
+    #[inline(always)]
+    ///Enables delivery of messages sent via `send_to` newest first, breaking FIFO order.
+    ///
+    ///Registry drains its queue, buffering messages, and delivers them in reverse order once queue
+    ///is empty (or `poll_budget` is exhausted), which reduces latency of fresh data when registry
+    ///falls behind (e.g. real-time dashboards). Order is reversed only within drained batch.
+    ///
+    ///As with `coalesce`, any other operation (e.g. `subscribe`) takes effect only after all
+    ///previously sent messages are delivered. It has no effect if `coalesce` or `prioritize` is
+    ///enabled, as they define their own order.
+    pub fn lifo(mut self) -> Self {
+        self.lifo = Some(false);
+        self
+    }
+
+    #[inline(always)]
+    ///Enables delivery of messages sent via `send_to` newest first, keeping only latest message per key.
+    ///
+    ///Refer to `lifo` for details.
+    pub fn lifo_latest(mut self) -> Self {
+        self.lifo = Some(true);
+        self
+    }
+
+    #[inline(always)]
+    ///Enables delivery of messages in order of priority, set via `Channel::send_to_prio`.
+    ///
+    ///Registry drains its queue, buffering messages, and delivers them once queue is empty (or
+    ///`poll_budget` is exhausted), highest priority first, while messages of the same priority
+    ///keep their order. Messages sent via `send_to` have lowest priority `0`, unless they are
+    ///coalesced, in which case they are delivered after prioritized ones.
+    ///
+    ///As with `coalesce`, any other operation (e.g. `subscribe`) takes effect only after all
+    ///previously sent messages are delivered. Buffer holds whole drained batch, so its size is
+    ///limited only by `poll_budget`.
+    pub fn prioritize(mut self) -> Self {
+        self.prioritize = true;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets `transport` to deliver messages from `Channel` to `Registry`, instead of std mpsc.
+    ///
+    ///Takes precedence over `drop_oldest`.
+    pub fn transport<X: Transport<Envelope<K, T, S, M>>>(mut self, transport: X) -> Self where X::Sender: 'static, X::Receiver: 'static {
+        self.transport = Some(queue::custom(transport));
+        self
+    }
+
+    ///Creates new registry returning sending channel and registry task
+    pub fn build(self) -> (Channel<K, T, S, M>, Registry<K, T, S, M>) {
+        let (channel, recv) = match (self.transport, self.drop_oldest) {
+            (Some(transport), _) => transport,
+            (None, Some(capacity)) => queue::bounded(capacity, self.dead_letter.is_some()),
+            (None, None) => queue::unbounded(),
+        };
+        let (control, control_recv) = match self.priority_control {
+            true => {
+                let (control, recv) = mpsc::channel();
+                (Some(control), Some(recv))
+            },
+            false => (None, None),
+        };
+        let state = Arc::new(State::new());
+        if let Some(metrics) = self.metrics {
+            metrics.attach(&state);
+        }
+        let chan = Channel {
+            channel: ManuallyDrop::new(channel),
+            control: ManuallyDrop::new(control),
+            state: state.clone(),
+        };
+        let mut subscribers = HashMap::with_capacity(core::cmp::max(self.capacity, self.subscribers.len()));
+        subscribers.extend(self.subscribers.into_iter().map(|(key, channel)| (key, Subscriber {
+            id: None,
+            channel,
+            meta: None,
+        })));
+        let empty = subscribers.is_empty();
+        state.subscribers.store(subscribers.len(), Ordering::Relaxed);
+        let registry = Registry {
+            state,
+            registry: subscribers,
+            broadcast: HashMap::new(),
+            groups: HashMap::new(),
+            shards: Default::default(),
+            dead_letter: self.dead_letter,
+            tap: None,
+            middleware: self.middleware,
+            factory: self.factory,
+            hooks: Hooks {
+                on_evict: self.on_evict,
+                events: self.events,
+                on_empty: self.on_empty,
+                on_nonempty: self.on_nonempty,
+                empty,
+            },
+            summary: Default::default(),
+            checkpoint: self.checkpoint.map(|(interval, hook)| Checkpoint {
+                interval,
+                next: std::time::Instant::now().checked_add(interval),
+                hook,
+            }),
+            poll_budget: self.poll_budget,
+            max_subscribers: self.max_subscribers,
+            max_keys_per_sender: self.max_keys_per_sender,
+            coalesce: match self.coalesce {
+                true => Some(HashMap::new()),
+                false => None,
+            },
+            lifo: self.lifo.map(|latest| match latest {
+                true => Lifo::Latest(HashMap::new(), 0),
+                false => Lifo::All(Vec::new()),
+            }),
+            priority: match self.prioritize {
+                true => Some(Priority {
+                    pending: Default::default(),
+                    seq: 0,
+                }),
+                false => None,
+            },
+            inflight: self.inflight.map(|(limit, clone_key)| Inflight {
+                limit,
+                clone_key,
+                pending: HashMap::new(),
+            }),
+            #[cfg(feature = "log")]
+            key_fmt: self.key_fmt,
+            #[cfg(feature = "tracing")]
+            trace_key: self.trace_key,
+            recv,
+            control: control_recv,
+        };
+        (chan, registry)
+    }
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M> RegistryBuilder<K, T, S, M> {
+    #[inline(always)]
+    ///Sets `sink` to receive lifecycle events of subscribers.
+    ///
+    ///Events are sent on registry's thread, so `sink` should not block. Sink is removed once it
+    ///is closed.
+    pub fn events<E: Sender<RegistryEvent<K>> + Send + 'static>(mut self, sink: E) -> Self where RegistryEvent<K>: Send {
+        self.events = Some(Box::new(move |event: RegistryEvent<&K>| sink.try_send(event.cloned()).is_ok()));
+        self
+    }
+
+    #[inline(always)]
+    ///Sets maximum number of messages to be buffered per key, while its subscriber is full.
+    ///
+    ///By default message, rejected by full subscriber (e.g. `mpsc::SyncSender`), is passed to dead
+    ///letter sink. With this option registry keeps up to `max` such messages per key and retries
+    ///them in order, before any new message to the same key, once registry is polled or woken
+    ///again. Messages beyond limit are passed to dead letter sink, as well as buffered messages of
+    ///key, which subscriber is gone.
+    ///
+    ///As registry processes its queue on single thread, buffering doesn't block other keys, but
+    ///buffered message is only retried on next message, so slow subscriber may wait for it until
+    ///registry is woken. Buffered message is considered delivered (e.g. by `send_to_confirm_blocking`).
+    ///Broadcast groups and shards are not buffered, skipping full subscribers instead.
+    pub fn max_inflight_per_key(mut self, max: usize) -> Self {
+        self.inflight = Some((max, K::clone));
+        self
+    }
+
+    #[inline(always)]
+    ///Sets `factory` to create subscriber of key without one, once message is sent to such key.
+    ///
+    ///This allows to create subscribers lazily (e.g. handler of entity, spawned on first message
+    ///to it). Created subscriber is subscribed, as if via `Channel::subscribe`, and receives message.
+    ///If `factory` returns `None` or subscriber is rejected (e.g. due to `max_subscribers`), message
+    ///is passed to dead letter sink or dropped, as usual.
+    ///
+    ///Factory is invoked only to deliver message to single key (e.g. `send_to`), hence broadcasts,
+    ///`send_to_if_present` and `send_to_with` do not create subscribers.
+    ///Factory runs on registry's thread for every message to vacant key, blocking registry while
+    ///running, so it should be quick and should return `None` for keys, that are not meant to exist.
+    pub fn subscriber_factory<F: FnMut(&K) -> Option<S> + Send + 'static>(mut self, factory: F) -> Self {
+        self.factory = Some((Box::new(factory), K::clone));
+        self
+    }
+}
+
+#[cfg(feature = "log")]
+impl<K: PartialEq + Eq + Hash + fmt::Debug, T: Send, S: Sender<T>, M> RegistryBuilder<K, T, S, M> {
+    #[inline(always)]
+    ///Includes key into log of messages, dropped due to unknown key.
+    pub fn log_keys(mut self) -> Self {
+        self.key_fmt = Some(K::fmt);
+        self
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<K: PartialEq + Eq + Hash + fmt::Display, T: Send, S: Sender<T>, M> RegistryBuilder<K, T, S, M> {
+    #[inline(always)]
+    ///Records key into span of every handled message, that has key.
+    pub fn trace_keys(mut self) -> Self {
+        self.trace_key = Some(<K as fmt::Display>::fmt);
+        self
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Default for RegistryBuilder<K, T, S, M> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::with_meta()
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> fmt::Debug for RegistryBuilder<K, T, S, M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("RegistryBuilder")
+           .field("capacity", &self.capacity)
+           .field("subscribers", &self.subscribers.len())
+           .field("dead_letter", &self.dead_letter.is_some())
+           .field("on_evict", &self.on_evict.is_some())
+           .field("events", &self.events.is_some())
+           .field("on_empty", &self.on_empty.is_some())
+           .field("on_nonempty", &self.on_nonempty.is_some())
+           .field("middleware", &self.middleware.is_some())
+           .field("factory", &self.factory.is_some())
+           .field("checkpoint", &self.checkpoint.as_ref().map(|(interval, _)| interval))
+           .field("poll_budget", &self.poll_budget)
+           .field("max_subscribers", &self.max_subscribers)
+           .field("max_keys_per_sender", &self.max_keys_per_sender)
+           .field("drop_oldest", &self.drop_oldest)
+           .field("priority_control", &self.priority_control)
+           .field("coalesce", &self.coalesce)
+           .field("lifo", &self.lifo.is_some())
+           .field("max_inflight_per_key", &self.inflight.map(|(limit, _)| limit))
+           .field("prioritize", &self.prioritize)
+           .field("transport", &self.transport.is_some())
+           .field("metrics", &self.metrics.is_some())
+           .finish()
+    }
+}
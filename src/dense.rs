@@ -0,0 +1,171 @@
+use core::{fmt, task};
+use core::future::Future;
+use core::pin::Pin;
+
+use crate::driver::{self, Driver};
+use crate::{deliver, Cancelled, Sender};
+
+pub(crate) enum DenseMessage<T: Send, S: Sender<T>> {
+    Subscribe(usize, S),
+    Unsubscribe(usize),
+    Msg(usize, T),
+}
+
+///Channel of `DenseRegistry`, addressing subscribers by index.
+pub struct DenseChannel<T: Send, S: Sender<T>> {
+    max_key: usize,
+    channel: driver::Tx<DenseMessage<T, S>>,
+}
+
+impl<T: Send, S: Sender<T>> DenseChannel<T, S> {
+    #[inline(always)]
+    ///Returns greatest key, that can be subscribed
+    pub fn max_key(&self) -> usize {
+        self.max_key
+    }
+
+    #[inline]
+    ///Subscribes provided `channel` with specified `key`, potentially removing existing channel
+    ///
+    ///Panics if `key` is greater than `max_key`.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe(&self, key: usize, channel: S) -> Result<(), Cancelled> {
+        assert!(key <= self.max_key, "Key must not be greater than max_key");
+        self.channel.send(DenseMessage::Subscribe(key, channel))
+    }
+
+    #[inline(always)]
+    ///Removes `channel` with specified `key` from registry
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn unsubscribe(&self, key: usize) -> Result<(), Cancelled> {
+        self.channel.send(DenseMessage::Unsubscribe(key))
+    }
+
+    #[inline(always)]
+    ///Sends message `msg` to channel with specified `key`
+    ///
+    ///Message to key without subscriber, including keys greater than `max_key`, is dropped.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to(&self, key: usize, msg: T) -> Result<(), Cancelled> {
+        self.channel.send(DenseMessage::Msg(key, msg))
+    }
+}
+
+impl<T: Send, S: Sender<T>> Clone for DenseChannel<T, S> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            max_key: self.max_key,
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T: Send, S: Sender<T>> fmt::Debug for DenseChannel<T, S> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("DenseChannel")
+           .field("id", &self.channel.state.id)
+           .field("max_key", &self.max_key)
+           .finish()
+    }
+}
+
+///Registry of subscribers, indexed by key within `0..=max_key`.
+///
+///This is lightweight alternative to `Registry` for small key domains with single subscriber per
+///key, which stores subscribers in `Vec`, avoiding hashing of keys.
+///It only supports subscription and direct delivery of messages.
+///
+///As `Registry`, it can be either ran on dedicated thread via `run` or polled as `Future`.
+pub struct DenseRegistry<T: Send, S: Sender<T>> {
+    registry: Vec<Option<S>>,
+    recv: driver::Rx<DenseMessage<T, S>>,
+}
+
+impl<T: Send, S: Sender<T>> DenseRegistry<T, S> {
+    ///Process registry messages until cancelled.
+    ///
+    ///This function blocks, until all sending channels gets closed
+    pub fn run(&mut self) -> Cancelled {
+        self.run_queue()
+    }
+
+    ///Processes all queued messages, returning subscribers indexed by key.
+    pub fn finish(mut self) -> Vec<Option<S>> {
+        while let Ok(message) = self.recv.try_recv() {
+            self.handle(message);
+        }
+        core::mem::take(&mut self.registry)
+    }
+}
+
+impl<T: Send, S: Sender<T>> Driver for DenseRegistry<T, S> {
+    type Message = DenseMessage<T, S>;
+
+    #[inline(always)]
+    fn rx(&self) -> &driver::Rx<Self::Message> {
+        &self.recv
+    }
+
+    fn handle(&mut self, message: DenseMessage<T, S>) {
+        match message {
+            DenseMessage::Subscribe(key, channel) => {
+                self.registry[key] = Some(channel);
+            },
+            DenseMessage::Unsubscribe(key) => if let Some(subscriber) = self.registry.get_mut(key) {
+                *subscriber = None;
+            },
+            DenseMessage::Msg(key, message) => if let Some(subscriber) = self.registry.get_mut(key) {
+                if let Some(channel) = subscriber.as_ref() {
+                    match deliver(channel, message) {
+                        Ok(()) => self.recv.state().on_delivered(1),
+                        Err(Some(error)) if !error.kind.is_closed() => (),
+                        Err(_) => *subscriber = None,
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl<T: Send, S: Sender<T>> fmt::Debug for DenseRegistry<T, S> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("DenseRegistry")
+           .field("max_key", &self.registry.len().saturating_sub(1))
+           .finish()
+    }
+}
+
+impl<T: Send, S: Sender<T>> Unpin for DenseRegistry<T, S> {}
+
+impl<T: Send, S: Sender<T>> Future for DenseRegistry<T, S> {
+    type Output = Cancelled;
+
+    #[inline(always)]
+    fn poll(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        self.get_mut().poll_queue(ctx.waker())
+    }
+}
+
+///Creates new registry for keys within `0..=max_key`, returning sending channel and registry task
+///
+///Refer to `DenseRegistry` for details.
+pub fn dense_registry<T: Send, S: Sender<T>>(max_key: usize) -> (DenseChannel<T, S>, DenseRegistry<T, S>) {
+    let (channel, recv) = driver::channel();
+    let mut registry = Vec::new();
+    registry.resize_with(max_key.saturating_add(1), || None);
+
+    (DenseChannel {
+        max_key,
+        channel,
+    }, DenseRegistry {
+        registry,
+        recv,
+    })
+}
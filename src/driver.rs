@@ -0,0 +1,170 @@
+//Queue and processing loop, shared by lightweight registries (`DenseRegistry`, `LinearRegistry`, `BatchRegistry`)
+
+use core::task;
+use core::mem::ManuallyDrop;
+use core::sync::atomic::{self, Ordering};
+use std::sync::{mpsc, Arc};
+
+use crate::{waker, Cancelled, State};
+
+//Sending half of registry's queue, counted as one of its channels
+pub(crate) struct Tx<M> {
+    pub(crate) state: Arc<State>,
+    channel: ManuallyDrop<mpsc::Sender<M>>,
+}
+
+impl<M> Tx<M> {
+    pub(crate) fn send(&self, msg: M) -> Result<(), Cancelled> {
+        match self.channel.send(msg) {
+            Ok(()) => {
+                //Registry, that is draining its queue, picks message up without being woken
+                atomic::fence(Ordering::SeqCst);
+                if !self.state.running.load(Ordering::Relaxed) {
+                    self.state.waker.wake();
+                }
+                Ok(())
+            },
+            Err(_) => Err(Cancelled::NOT_RUNNING),
+        }
+    }
+}
+
+impl<M> Clone for Tx<M> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        self.state.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            state: self.state.clone(),
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<M> Drop for Tx<M> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        //Drop channel pipe first, to ensure it gets broken on receiver task
+        unsafe {
+            ManuallyDrop::drop(&mut self.channel);
+        }
+
+        if self.state.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.state.waker.wake();
+        }
+    }
+}
+
+//Receiving half of registry's queue, owned by registry
+pub(crate) struct Rx<M> {
+    state: Arc<State>,
+    recv: mpsc::Receiver<M>,
+}
+
+impl<M> Rx<M> {
+    #[inline(always)]
+    pub(crate) fn state(&self) -> &State {
+        &self.state
+    }
+
+    #[inline(always)]
+    pub(crate) fn try_recv(&self) -> Result<M, mpsc::TryRecvError> {
+        self.recv.try_recv()
+    }
+}
+
+impl<M> Drop for Rx<M> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.state.closed.store(true, Ordering::Release);
+        //Channels, that outlive registry, must not wake task, that may be gone already
+        self.state.waker.clear();
+        self.state.ready.stop();
+        self.state.finished.start();
+    }
+}
+
+pub(crate) fn channel<M>() -> (Tx<M>, Rx<M>) {
+    let (tx, recv) = mpsc::channel();
+    let state = Arc::new(State::new());
+
+    (Tx {
+        state: state.clone(),
+        channel: ManuallyDrop::new(tx),
+    }, Rx {
+        state,
+        recv,
+    })
+}
+
+//Registry, driven by messages of its queue
+pub(crate) trait Driver {
+    type Message;
+
+    fn rx(&self) -> &Rx<Self::Message>;
+
+    fn handle(&mut self, message: Self::Message);
+
+    //Invoked once queue is drained, right before registry becomes idle
+    #[inline(always)]
+    fn drained(&mut self) {
+    }
+
+    //Invoked once all channels are gone, before registry is finished
+    #[inline(always)]
+    fn disconnected(&mut self) {
+    }
+
+    //Parks thread of `run_queue` until registry is woken
+    #[inline(always)]
+    fn park(&self) {
+        std::thread::park();
+    }
+
+    fn poll_queue(&mut self, waker: &task::Waker) -> task::Poll<Cancelled> {
+        self.rx().state.ready.start();
+        self.rx().state.running.store(true, Ordering::Relaxed);
+        loop {
+            let message = match self.rx().try_recv() {
+                Ok(message) => message,
+                Err(mpsc::TryRecvError::Empty) => {
+                    self.drained();
+                    let rx = self.rx();
+                    rx.state.waker.register_ref(waker);
+                    rx.state.running.store(false, Ordering::Relaxed);
+                    //Pairs with fence in `Tx::send`: either sender observes registry as idle
+                    //and wakes it, or its message is visible to this check.
+                    atomic::fence(Ordering::SeqCst);
+                    match rx.try_recv() {
+                        Ok(message) => {
+                            rx.state.running.store(true, Ordering::Relaxed);
+                            message
+                        },
+                        Err(mpsc::TryRecvError::Empty) => break task::Poll::Pending,
+                        Err(mpsc::TryRecvError::Disconnected) => break self.finish_queue(),
+                    }
+                },
+                Err(mpsc::TryRecvError::Disconnected) => break self.finish_queue(),
+            };
+            self.handle(message);
+        }
+    }
+
+    #[inline(always)]
+    fn finish_queue(&mut self) -> task::Poll<Cancelled> {
+        self.disconnected();
+        self.rx().state.running.store(false, Ordering::Relaxed);
+        self.rx().state.finished.start();
+        task::Poll::Ready(Cancelled::DISCONNECTED)
+    }
+
+    fn run_queue(&mut self) -> Cancelled {
+        let waker = waker::thread::waker(std::thread::current());
+
+        loop {
+            match self.poll_queue(&waker) {
+                task::Poll::Ready(error) => break error,
+                task::Poll::Pending => self.park(),
+            }
+        }
+    }
+}
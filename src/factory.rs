@@ -0,0 +1,48 @@
+use core::fmt;
+use core::hash::Hash;
+
+use crate::{Channel, Sender};
+
+///Factory of `Channel` instances, allowing to create them on demand without holding one.
+///
+///Factory counts as channel itself, hence it keeps registry running as long as it exists,
+///even if there are no other channels.
+pub struct ChannelFactory<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M = ()> {
+    channel: Channel<K, T, S, M>,
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> ChannelFactory<K, T, S, M> {
+    #[inline(always)]
+    ///Creates new channel of the registry
+    pub fn channel(&self) -> Channel<K, T, S, M> {
+        self.channel.clone()
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Channel<K, T, S, M> {
+    #[inline(always)]
+    ///Converts channel into factory of channels.
+    pub fn into_factory(self) -> ChannelFactory<K, T, S, M> {
+        ChannelFactory {
+            channel: self,
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Clone for ChannelFactory<K, T, S, M> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> fmt::Debug for ChannelFactory<K, T, S, M> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ChannelFactory")
+           .field("channel", &self.channel)
+           .finish()
+    }
+}
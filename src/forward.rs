@@ -0,0 +1,55 @@
+use core::hash::Hash;
+
+use crate::{Channel, Sender, SendError, SendErrorKind};
+
+///Sender, which forwards messages to `key` of another registry.
+///
+///Subscribing it allows to build routing chains, including within the same registry.
+///When registry is gone, sender reports itself as closed.
+///
+///Note that it holds `Channel`, so subscribing it to the same registry keeps registry running
+///until it is unsubscribed.
+pub struct ForwardSender<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M = ()> {
+    key: K,
+    channel: Channel<K, T, S, M>,
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M> ForwardSender<K, T, S, M> {
+    #[inline(always)]
+    ///Creates new sender forwarding messages into `key` of `channel`'s registry
+    pub fn new(channel: Channel<K, T, S, M>, key: K) -> Self {
+        Self {
+            key,
+            channel,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns key, messages are forwarded to
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M> Sender<T> for ForwardSender<K, T, S, M> {
+    #[inline]
+    fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.channel.send_msg(self.key.clone(), value) {
+            Ok(()) => Ok(()),
+            Err(message) => Err(SendError {
+                kind: SendErrorKind::Closed,
+                message,
+            })
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M> Clone for ForwardSender<K, T, S, M> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            channel: self.channel.clone(),
+        }
+    }
+}
@@ -1,26 +1,110 @@
 //! Addressable channel registry
+//!
+//! ## Features
+//!
+//! - `panic-safe` - Catches panics of `Sender::try_send`, evicting panicked subscriber (reported via
+//! `RegistryBuilder::on_evict`) instead of terminating registry. Subscribers are assumed to be
+//! unwind safe: as the panicked subscriber is removed, its potentially broken state is never
+//! observed by registry again, but any state it shares with other code (e.g. through `Arc`) is left
+//! as it was at the moment of panic.
+//! - `topic` - Provides `Topic` key wrapper and `Channel::topic` to create sender bound to topic.
+//! - `testing` - Provides `testing` module with utilities to test routing and `Registry::step` to
+//! drive registry one operation at a time.
+//! - `tracing` - Handles every message within `tracing` span on debug level, recording operation.
+//! Use `RegistryBuilder::trace_keys` to record key too.
+//! - `log` - Logs messages dropped due to unknown key on debug level. Use `RegistryBuilder::log_keys`
+//! to include key into log.
+//! - `local` - Provides `LocalRegistry` for single-threaded executors, which doesn't require `Send`.
+//! - `metrics` - Counts how often registry's waker is cloned on registration, exposed via
+//! `Channel::waker_stats`, in order to diagnose executors, that hand out wakers failing `Waker::will_wake`.
+//! - `blocking` - `Registry::run` blocks on receiving from unbounded queue, instead of parking until
+//! woken. It applies only when nothing but messages can wake registry: no `Registry::stop_handle`,
+//! checkpoint or control lane. Otherwise `run` behaves as without this feature.
+//! - `serde` - Provides `Op`, wire representation of keyed routing operations, with `encode_op` and
+//! `decode_op` to bridge registry across processes. Subscribers are local to process, so they
+//! are created by receiving side via `Channel::apply_op`.
+//!
+//! ## Ordering
+//!
+//! Registry handles operations one by one, in order of its queue. Hence operations, sent via the
+//! same `Channel` handle, take effect in order they are sent: message sent after `subscribe`
+//! is delivered to new subscriber, message sent before `unsubscribe` or `rekey` is delivered to
+//! old key, and messages to the same subscriber arrive in order of `send_to` calls.
+//!
+//! Cloned handles share the same queue, so operations from different handles are ordered by the
+//! moment they are enqueued. There is no ordering between handles used concurrently, unless it
+//! is established by other means (e.g. by joining thread, that used other handle).
+//!
+//! Following options relax this contract, as described by each of them:
+//! `RegistryBuilder::priority_control`, `RegistryBuilder::coalesce`, `RegistryBuilder::prioritize`, `RegistryBuilder::lifo`,
+//! `RegistryBuilder::drop_oldest` and `RegistryBuilder::max_inflight_per_key`.
 
 #![warn(missing_docs)]
-#![cfg_attr(feature = "cargo-clippy", allow(clippy::style))]
+#![allow(clippy::style, clippy::type_complexity)]
 
+mod macros;
 mod waker;
+mod oneshot;
+mod ready;
+mod queue;
+mod transport;
+pub use transport::{Envelope, Transport, TransportSender, TransportReceiver};
+mod bound;
+pub use bound::BoundSender;
+mod weak;
+pub use weak::WeakChannel;
+mod subscription;
+pub use subscription::Subscription;
+mod forward;
+pub use forward::ForwardSender;
+mod actor;
+pub use actor::Actor;
+mod factory;
+pub use factory::ChannelFactory;
+mod driver;
+mod dense;
+pub use dense::{dense_registry, DenseChannel, DenseRegistry};
+mod linear;
+pub use linear::{linear_registry, LinearChannel, LinearRegistry};
+mod batch;
+pub use batch::{batching_registry, BatchChannel, BatchRegistry};
+mod builder;
+pub use builder::RegistryBuilder;
+mod metrics;
+pub use metrics::{Metrics, MetricsSnapshot};
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "serde")]
+mod wire;
+#[cfg(feature = "serde")]
+pub use wire::{decode_op, encode_op, Op};
+#[cfg(feature = "topic")]
+mod topic;
+#[cfg(feature = "topic")]
+pub use topic::Topic;
+#[cfg(feature = "local")]
+mod local;
+#[cfg(feature = "local")]
+pub use local::{local_registry, LocalChannel, LocalRegistry};
 
-use core::{fmt, task};
+use core::{fmt, task, time};
 use core::pin::Pin;
 use core::future::Future;
 use core::hash::Hash;
 use core::mem::ManuallyDrop;
 use std::sync::mpsc;
 use std::sync::Arc;
-use std::collections::{HashMap, hash_map};
+use core::sync::atomic::{self, AtomicBool, AtomicUsize, AtomicU64, Ordering};
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque, btree_map, hash_map};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 ///Describes sending error
 pub enum SendErrorKind {
-    //For now do not allow bounded channels to avoid dealing with back-pressure
-    //Once strategy found, consider implementing it
-    /////Capacity overflow
-    //Full,
+    ///Capacity overflow
+    ///
+    ///Subscriber is kept, but message is not delivered, unless registry buffers it as configured
+    ///via `RegistryBuilder::max_inflight_per_key`.
+    Full,
     ///Remote end is closed
     Closed
 }
@@ -30,7 +114,7 @@ impl SendErrorKind {
     pub const fn is_closed(&self) -> bool {
         match self {
             SendErrorKind::Closed => true,
-            //SendErrorKind::Full => false,
+            SendErrorKind::Full => false,
         }
     }
 }
@@ -59,8 +143,36 @@ impl<T> fmt::Display for SendError<T> {
 
 impl<T> std::error::Error for SendError<T> {}
 
+impl<T> From<mpsc::SendError<T>> for SendError<T> {
+    #[inline(always)]
+    fn from(error: mpsc::SendError<T>) -> Self {
+        Self {
+            kind: SendErrorKind::Closed,
+            message: error.0
+        }
+    }
+}
+
+impl<T> From<mpsc::TrySendError<T>> for SendError<T> {
+    #[inline(always)]
+    fn from(error: mpsc::TrySendError<T>) -> Self {
+        match error {
+            mpsc::TrySendError::Full(message) => Self {
+                kind: SendErrorKind::Full,
+                message
+            },
+            mpsc::TrySendError::Disconnected(message) => Self {
+                kind: SendErrorKind::Closed,
+                message
+            },
+        }
+    }
+}
+
 ///Channel sender
-pub trait Sender<T: Send> {
+///
+///Message is not required to be `Send`, but `Registry` and `Channel` require it to move between threads.
+pub trait Sender<T> {
     //#[inline(always)]
     /////Send method
     /////
@@ -72,226 +184,2939 @@ pub trait Sender<T: Send> {
     ///Attempts to deliver message to remote end, and is expected to be successful as long as
     ///remote end has not shut down.
     fn try_send(&self, value: T) -> Result<(), SendError<T>>;
+
+    #[inline(always)]
+    ///Returns `true` if remote end is known to be shut down.
+    ///
+    ///Used by registry to remove subscribers proactively, without waiting for failed delivery.
+    ///Defaults to `false`, in which case subscriber is removed only once `try_send` fails.
+    fn is_closed(&self) -> bool {
+        false
+    }
+}
+
+//Allows to subscribe borrowed channels, e.g. within `std::thread::scope`
+impl<T, S: Sender<T> + ?Sized> Sender<T> for &S {
+    #[inline(always)]
+    fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        (**self).try_send(value)
+    }
+
+    #[inline(always)]
+    fn is_closed(&self) -> bool {
+        (**self).is_closed()
+    }
+}
+
+//Allows to subscribe heterogeneous channels as `Box<dyn Sender<T> + Send>`
+impl<T, S: Sender<T> + ?Sized> Sender<T> for Box<S> {
+    #[inline(always)]
+    fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        (**self).try_send(value)
+    }
+
+    #[inline(always)]
+    fn is_closed(&self) -> bool {
+        (**self).is_closed()
+    }
+}
+
+impl<T, S: Sender<T> + ?Sized> Sender<T> for Arc<S> {
+    #[inline(always)]
+    fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        (**self).try_send(value)
+    }
+
+    #[inline(always)]
+    fn is_closed(&self) -> bool {
+        (**self).is_closed()
+    }
 }
 
-impl<T: Send> Sender<T> for mpsc::Sender<T> {
+impl<T> Sender<T> for mpsc::Sender<T> {
     #[inline]
     fn try_send(&self, value: T) -> Result<(), SendError<T>> {
-        match mpsc::Sender::send(self, value) {
-            Ok(()) => Ok(()),
-            Err(error) => Err(SendError {
-                kind: SendErrorKind::Closed,
-                message: error.0
-            })
+        mpsc::Sender::send(self, value).map_err(Into::into)
+    }
+}
+
+//Bounded channel, that never blocks registry, reporting `SendErrorKind::Full` instead
+impl<T> Sender<T> for mpsc::SyncSender<T> {
+    #[inline]
+    fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        mpsc::SyncSender::try_send(self, value).map_err(Into::into)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+///User supplied identifier of subscriber.
+///
+///Allows to remove all subscriptions of the same subscriber without knowing its keys.
+pub struct SubscriberId(pub u64);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+///Lifecycle event of subscriber, reported via `RegistryBuilder::events`
+pub enum RegistryEvent<K> {
+    ///Channel is subscribed with key, either directly or into broadcast group
+    Subscribed(K),
+    ///Channel of key is unsubscribed
+    Unsubscribed(K),
+    ///Channel of key is removed due to being closed
+    Evicted(K),
+}
+
+impl<K> RegistryEvent<K> {
+    #[inline(always)]
+    ///Returns key of event
+    pub fn key(&self) -> &K {
+        match self {
+            RegistryEvent::Subscribed(key) => key,
+            RegistryEvent::Unsubscribed(key) => key,
+            RegistryEvent::Evicted(key) => key,
         }
     }
 }
 
-enum Message<K: PartialEq + Eq, T: Send, S: Sender<T>> {
-    Subscribe(K, S),
-    Unsubscribe(K),
-    Msg(K, T)
+impl<K: Clone> RegistryEvent<&K> {
+    #[inline(always)]
+    ///Creates event with owned key
+    pub fn cloned(self) -> RegistryEvent<K> {
+        match self {
+            RegistryEvent::Subscribed(key) => RegistryEvent::Subscribed(key.clone()),
+            RegistryEvent::Unsubscribed(key) => RegistryEvent::Unsubscribed(key.clone()),
+            RegistryEvent::Evicted(key) => RegistryEvent::Evicted(key.clone()),
+        }
+    }
 }
 
-struct State {
-    waker: waker::AtomicWaker,
+struct Subscriber<S, M> {
+    id: Option<SubscriberId>,
+    channel: S,
+    //Metadata, attached via `Channel::subscribe_with_meta`
+    meta: Option<M>,
 }
 
-impl State {
-    fn new() -> Self {
-        Self {
-            waker: waker::AtomicWaker::new(),
+//Control operations must be dedicated variants, so that no key value is reserved
+enum Message<K: PartialEq + Eq, T: Send, S: Sender<T>, M> {
+    Subscribe(K, Subscriber<S, M>, Option<oneshot::Sender<bool>>),
+    SubscribeMany(Vec<K>, S, fn(&S) -> S),
+    UnsubscribeId(SubscriberId),
+    IsSubscribed(SubscriberId, oneshot::Sender<bool>),
+    SubscribeBroadcast(K, S),
+    SubscribeTap(S, fn(&T) -> T),
+    SubscribeGroup(K, S),
+    Unsubscribe(K),
+    Rekey(K, K, Option<oneshot::Sender<bool>>),
+    Msg(K, T),
+    MsgConfirm(K, T, oneshot::Sender<bool>),
+    MsgResult(K, T, oneshot::Sender<Result<(), SubscriberGone>>),
+    MsgIfPresent(K, T, oneshot::Sender<SendOutcome<T>>),
+    MsgTtl(K, T, std::time::Instant),
+    MsgPrio(K, T, u8),
+    MsgOr(K, K, T),
+    MsgWith(K, Box<dyn FnOnce() -> T + Send>),
+    Broadcast(K, T, fn(&T) -> T, Option<oneshot::Sender<usize>>),
+    BroadcastAll(T, fn(&T) -> T),
+    MsgSelected(SelectFn<K>, T, fn(&T) -> T),
+    MsgMulti(Vec<K>, T, fn(&T) -> T),
+    SubscribeShard(u32, u32, S),
+    UnsubscribeShard(u32, u32),
+    Compact,
+    PruneClosed,
+    //Wakes registry, blocked on queue, without doing anything
+    #[cfg(feature = "blocking")]
+    Wake,
+    WithKeys(KeysFn<K>, oneshot::Sender<()>),
+    WithSubscribers(SubscribersFn<K, S, M>, oneshot::Sender<()>),
+    Retain(RetainFn<K>, Option<oneshot::Sender<usize>>),
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Message<K, T, S, M> {
+    //Marks request as accepted by registry, if it awaits reply
+    fn accept(&self) {
+        match self {
+            Message::Subscribe(_, _, Some(reply)) | Message::Rekey(_, _, Some(reply)) | Message::IsSubscribed(_, reply) | Message::MsgConfirm(_, _, reply) => reply.accept(),
+            Message::Broadcast(_, _, _, Some(reply)) | Message::Retain(_, Some(reply)) => reply.accept(),
+            Message::MsgResult(_, _, reply) => reply.accept(),
+            Message::MsgIfPresent(_, _, reply) => reply.accept(),
+            Message::WithKeys(_, reply) | Message::WithSubscribers(_, reply) => reply.accept(),
+            _ => (),
+        }
+    }
+
+    //Returns whether message is control operation, rather than message for subscribers
+    fn is_control(&self) -> bool {
+        match self {
+            Message::Msg(..) | Message::MsgConfirm(..) | Message::MsgResult(..) | Message::MsgIfPresent(..) | Message::MsgTtl(..) | Message::MsgPrio(..) | Message::MsgOr(..) | Message::MsgWith(..) | Message::Broadcast(..) | Message::BroadcastAll(..) | Message::MsgSelected(..) | Message::MsgMulti(..) => false,
+            _ => true,
         }
     }
 }
 
-///Indicates remote end has been dropped, making this end unusable
-pub struct Cancelled;
+///Registry operation, constructed without `Channel`.
+///
+///Allows to bridge registry behind another messaging layer (e.g. operations received from network),
+///which then pushes operations via `Channel::send_message`.
+pub struct RawMessage<K: PartialEq + Eq, T: Send, S: Sender<T>, M = ()>(Message<K, T, S, M>);
 
-impl fmt::Debug for Cancelled {
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> RawMessage<K, T, S, M> {
     #[inline(always)]
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(self, fmt)
+    ///Creates operation to subscribe `channel` with `key`, as `Channel::subscribe`
+    pub fn subscribe(key: K, channel: S) -> Self {
+        Self(Message::Subscribe(key, Subscriber {
+            id: None,
+            channel,
+            meta: None,
+        }, None))
+    }
+
+    #[inline(always)]
+    ///Creates operation to unsubscribe `key`, as `Channel::unsubscribe`
+    pub fn unsubscribe(key: K) -> Self {
+        Self(Message::Unsubscribe(key))
+    }
+
+    #[inline(always)]
+    ///Creates operation to send `msg` to `key`, as `Channel::send_to`
+    pub fn msg(key: K, msg: T) -> Self {
+        Self(Message::Msg(key, msg))
     }
 }
 
-impl fmt::Display for Cancelled {
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> fmt::Debug for RawMessage<K, T, S, M> {
     #[inline(always)]
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt.write_str("Cancelled")
+        fmt.write_str("RawMessage")
     }
 }
 
-impl std::error::Error for Cancelled {}
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Describes why operation is not queued by `Channel::push`
+pub enum PushErrorKind {
+    ///Queue of registry is full
+    Full,
+    ///Registry is not running
+    Cancelled,
+}
 
-#[must_use = "You must run Registry task"]
-///Task to manage messages within Registry
-///
-///It is expected running as either async task or on dedicated thread worker.
-///
-///This future is never ending, unless Registry gets dropped, resulting in error.
-pub struct Registry<K: PartialEq + Eq, T: Send, S: Sender<T>> {
-    state: Arc<State>,
-    registry: HashMap<K, S>,
-    recv: mpsc::Receiver<Message<K, T, S>>
+///Error of `Channel::push`
+pub struct PushError<K: PartialEq + Eq, T: Send, S: Sender<T>, M = ()> {
+    ///Error kind
+    pub kind: PushErrorKind,
+    ///Operation, that could not be queued
+    pub message: RawMessage<K, T, S, M>,
 }
 
-impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>> Registry<K, T, S> {
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> fmt::Debug for PushError<K, T, S, M> {
     #[inline(always)]
-    fn new(state: Arc<State>, recv: mpsc::Receiver<Message<K, T, S>>) -> Self {
-        Self {
-            state,
-            registry: HashMap::new(),
-            recv,
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.kind, fmt)
+    }
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> fmt::Display for PushError<K, T, S, M> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(match self.kind {
+            PushErrorKind::Full => "queue of registry is full",
+            PushErrorKind::Cancelled => CancelReason::NotRunning.as_str(),
+        })
+    }
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> std::error::Error for PushError<K, T, S, M> {}
+
+#[cfg(feature = "tracing")]
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Message<K, T, S, M> {
+    //Returns name of operation and its key, if any
+    fn operation(&self) -> (&'static str, Option<&K>) {
+        match self {
+            Message::Subscribe(key, _, _) => ("subscribe", Some(key)),
+            Message::SubscribeMany(_, _, _) => ("subscribe_many", None),
+            Message::UnsubscribeId(_) => ("unsubscribe_id", None),
+            Message::IsSubscribed(..) => ("is_subscribed", None),
+            Message::SubscribeBroadcast(key, _) => ("subscribe_broadcast", Some(key)),
+            Message::SubscribeTap(..) => ("subscribe_all", None),
+            Message::SubscribeGroup(key, _) => ("subscribe_group", Some(key)),
+            Message::Unsubscribe(key) => ("unsubscribe", Some(key)),
+            Message::Rekey(key, _, _) => ("rekey", Some(key)),
+            Message::Msg(key, _) => ("msg", Some(key)),
+            Message::MsgConfirm(key, _, _) => ("msg_confirm", Some(key)),
+            Message::MsgResult(key, _, _) => ("msg_result", Some(key)),
+            Message::MsgIfPresent(key, _, _) => ("msg_if_present", Some(key)),
+            Message::MsgTtl(key, _, _) => ("msg_ttl", Some(key)),
+            Message::MsgPrio(key, _, _) => ("msg_prio", Some(key)),
+            Message::MsgOr(key, _, _) => ("msg_or", Some(key)),
+            Message::MsgWith(key, _) => ("msg_with", Some(key)),
+            Message::Broadcast(key, _, _, _) => ("broadcast", Some(key)),
+            Message::BroadcastAll(_, _) => ("broadcast_all", None),
+            Message::MsgSelected(..) => ("msg_selected", None),
+            Message::MsgMulti(..) => ("msg_multi", None),
+            Message::SubscribeShard(_, _, _) => ("subscribe_shard", None),
+            Message::UnsubscribeShard(_, _) => ("unsubscribe_shard", None),
+            Message::Compact => ("compact", None),
+            Message::PruneClosed => ("prune_closed", None),
+            #[cfg(feature = "blocking")]
+            Message::Wake => ("wake", None),
+            Message::WithKeys(..) => ("with_keys", None),
+            Message::WithSubscribers(..) => ("with_subscribers", None),
+            Message::Retain(..) => ("retain", None),
         }
     }
+}
 
-    ///Process registry messages until cancelled.
-    ///
-    ///This function blocks, until all sending channels gets closed
-    pub fn run(&mut self) -> Cancelled {
-        let waker = waker::thread::waker(std::thread::current());
+//Delivers `message` to the `channel`.
+//
+//Returns `Err(None)` if `channel` panicked, in which case message is lost.
+#[inline(always)]
+fn deliver<T, S: Sender<T>>(channel: &S, message: T) -> Result<(), Option<SendError<T>>> {
+    #[cfg(feature = "panic-safe")]
+    {
+        //Subscriber is evicted on panic, so no broken invariant can be observed afterwards
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| channel.try_send(message))) {
+            Ok(result) => result.map_err(Some),
+            Err(_) => Err(None),
+        }
+    }
 
-        loop {
-            match self.process(&waker) {
-                task::Poll::Ready(error) => break error,
-                task::Poll::Pending => std::thread::park(),
+    #[cfg(not(feature = "panic-safe"))]
+    {
+        channel.try_send(message).map_err(Some)
+    }
+}
 
-            }
+//Takes copy of `message` for tap, if one is set
+#[inline(always)]
+fn tap_copy<T: Send, S: Sender<T>>(tap: &Option<(S, fn(&T) -> T)>, message: &T) -> Option<T> {
+    tap.as_ref().map(|(_, clone)| clone(message))
+}
+
+//Passes copy of delivered message to tap, removing it if it is closed
+#[inline(always)]
+fn tap<T: Send, S: Sender<T>>(tap: &mut Option<(S, fn(&T) -> T)>, copy: Option<T>) {
+    if let (Some((channel, _)), Some(copy)) = (tap.as_ref(), copy) {
+        if !is_alive(deliver(channel, copy)) {
+            *tap = None;
         }
     }
+}
 
-    fn process(&mut self, waker: &task::Waker) -> task::Poll<Cancelled> {
-        loop {
-            match self.recv.try_recv() {
-                Ok(message) => match message {
-                    Message::Subscribe(key, channel) => {
-                        self.registry.insert(key, channel);
-                        continue
-                    }
-                    Message::Unsubscribe(key) => {
-                        self.registry.remove(&key);
-                        continue
-                    }
-                    Message::Msg(key, message) => match self.registry.entry(key) {
-                        hash_map::Entry::Occupied(entry) => match entry.get().try_send(message) {
-                            Ok(()) => continue,
-                            Err(error) => match error.kind {
-                                SendErrorKind::Closed => {
-                                    entry.remove();
-                                },
-                                //SendErrorKind::Full => {
-                                //    todo!();
-                                //}
-                            }
-                        },
-                        hash_map::Entry::Vacant(_) => continue,
-                    }
+//Returns whether channel should be kept after delivery attempt
+#[inline(always)]
+fn is_alive<T>(result: Result<(), Option<SendError<T>>>) -> bool {
+    match result {
+        Ok(()) => true,
+        Err(None) => false,
+        Err(Some(error)) => match error.kind {
+            SendErrorKind::Closed => false,
+            SendErrorKind::Full => true,
+        }
+    }
+}
+
+//Delivers `message` to every channel, cloning it for all but the last one.
+//Closed channels are removed, returning number of channels that accepted message.
+fn broadcast<K, T: Send, S: Sender<T>>(key: &K, channels: &mut Vec<S>, message: T, clone: fn(&T) -> T, on_evict: &mut Hooks<K>) -> usize {
+    let mut delivered = 0;
+    let mut message = Some(message);
+    let mut idx = 0;
+    while let Some(channel) = channels.get(idx) {
+        let message = match idx + 1 == channels.len() {
+            true => message.take(),
+            false => message.as_ref().map(clone),
+        };
+        let message = match message {
+            Some(message) => message,
+            None => break,
+        };
+
+        match deliver(channel, message) {
+            Ok(()) => {
+                delivered += 1;
+                idx += 1;
+            },
+            Err(None) => {
+                channels.remove(idx);
+                evict(on_evict, key);
+            },
+            Err(Some(error)) => match error.kind {
+                SendErrorKind::Closed => {
+                    channels.remove(idx);
+                    evict(on_evict, key);
                 },
-                Err(mpsc::TryRecvError::Disconnected) => break task::Poll::Ready(Cancelled),
-                Err(mpsc::TryRecvError::Empty) => {
-                    self.state.waker.register_ref(waker);
-                    break task::Poll::Pending;
-                }
+                SendErrorKind::Full => idx += 1,
             }
         }
     }
-}
 
-impl<K: PartialEq + Eq + Hash + Unpin, T: Send, S: Sender<T> + Unpin> Future for Registry<K, T, S> {
-    type Output = Cancelled;
+    delivered
+}
 
-    #[inline(always)]
-    fn poll(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
-        let waker = ctx.waker();
-        self.get_mut().process(waker)
+//Delivers messages of `buffer` in order, until `channel` is full.
+//
+//Returns whether `channel` is still alive.
+fn drain_buffer<T: Send, S: Sender<T>>(channel: &S, buffer: &mut VecDeque<T>, state: &State, tap_channel: &mut Option<(S, fn(&T) -> T)>) -> bool {
+    while let Some(message) = buffer.pop_front() {
+        let copy = tap_copy(tap_channel, &message);
+        match deliver(channel, message) {
+            Ok(()) => {
+                state.on_delivered(1);
+                tap(tap_channel, copy);
+            },
+            Err(None) => return false,
+            Err(Some(error)) => match error.kind {
+                SendErrorKind::Closed => {
+                    buffer.push_front(error.message);
+                    return false;
+                },
+                SendErrorKind::Full => {
+                    buffer.push_front(error.message);
+                    break;
+                },
+            },
+        }
     }
+    true
 }
 
-///Channel registry
-///
-///This is communication pipe towards channel
-///As long as at least one instance exist, registry task will continue running
-pub struct Channel<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>> {
-    state: Arc<State>,
-    channel: ManuallyDrop<mpsc::Sender<Message<K, T, S>>>,
+//Reason message is not delivered by registry
+enum Undelivered<K, T> {
+    //Message is lost due to subscriber panic or middleware
+    Lost,
+    //Subscriber is closed or full
+    Rejected(T),
+    //Key has no subscriber
+    Unknown(K, T),
+}
+
+//Subscribers of the same key, sharing its messages
+struct Group<S> {
+    channels: Vec<S>,
+    //Index of channel to receive next message
+    cursor: usize,
 }
 
-impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>> Channel<K, T, S> {
-    fn send(&self, msg: Message<K, T, S>) -> Result<(), Cancelled> {
-        match self.channel.send(msg) {
+//Delivers `message` to next channel of `group` in round-robin order.
+//Closed channels are removed, passing message to the following one, as full channels are skipped.
+//
+//Returns result of delivery, `Err(Some(message))` if there is no channel left to accept message, and number of removed channels
+fn deliver_group<T: Send, S: Sender<T>>(group: &mut Group<S>, mut message: T) -> (Result<(), Option<T>>, usize) {
+    let mut evicted = 0;
+    let mut full = 0;
+    while group.channels.len() > full {
+        let idx = group.cursor % group.channels.len();
+        match deliver(&group.channels[idx], message) {
             Ok(()) => {
-                self.state.waker.wake();
-                Ok(())
+                group.cursor = idx + 1;
+                return (Ok(()), evicted);
+            },
+            Err(None) => {
+                group.channels.remove(idx);
+                group.cursor = idx;
+                return (Err(None), evicted + 1);
             },
-            Err(_) => Err(Cancelled)
+            Err(Some(error)) => match error.kind {
+                SendErrorKind::Closed => {
+                    group.channels.remove(idx);
+                    group.cursor = idx;
+                    evicted += 1;
+                    message = error.message;
+                },
+                SendErrorKind::Full => {
+                    group.cursor = idx + 1;
+                    full += 1;
+                    message = error.message;
+                },
+            }
         }
     }
 
+    (Err(Some(message)), evicted)
+}
+
+type EvictHook<K> = Box<dyn FnMut(&K) + Send>;
+type EmptyHook = Box<dyn FnMut() + Send>;
+type Middleware<K, T> = Box<dyn FnMut(&K, T) -> Option<T> + Send>;
+type SubscriberFactory<K, S> = Box<dyn FnMut(&K) -> Option<S> + Send>;
+type KeysFn<K> = Box<dyn FnOnce(&mut dyn Iterator<Item = &K>) + Send>;
+type SubscribersFn<K, S, M> = Box<dyn FnOnce(&mut dyn Iterator<Item = (&K, &S, Option<&M>)>) + Send>;
+type RetainFn<K> = Box<dyn FnMut(&K) -> bool + Send>;
+type SelectFn<K> = Box<dyn FnOnce(&[&K]) -> Vec<K> + Send>;
+type CheckpointHook<K> = Box<dyn FnMut(&[&K]) + Send>;
+//Returns whether hook should be kept
+type EventHook<K> = Box<dyn FnMut(RegistryEvent<&K>) -> bool + Send>;
+
+//Message, waiting to be delivered in order of priority
+struct Prioritized<K, T> {
+    priority: u8,
+    //Sequence number, preserving order of messages with the same priority
+    seq: u64,
+    key: K,
+    message: T,
+}
+
+impl<K, T> Prioritized<K, T> {
     #[inline(always)]
-    ///Subscribes provided `channel` with specified `key`, potentially removing existing channel.
-    ///
-    ///Returns `Ok(())` if registry is still running
-    ///Returns `Err(Cancelled)` if message ignored due to registry not running
-    pub fn subscribe(&self, key: K, channel: S) -> Result<(), Cancelled> {
-        self.send(Message::Subscribe(key, channel))
+    fn order(&self) -> (u8, core::cmp::Reverse<u64>) {
+        (self.priority, core::cmp::Reverse(self.seq))
     }
+}
 
+impl<K, T> PartialEq for Prioritized<K, T> {
     #[inline(always)]
-    ///Removes `channel` with specified `key` from registry
-    ///
-    ///Returns `Ok(())` if registry is still running
-    ///Returns `Err(Cancelled)` if message ignored due to registry not running
-    pub fn unsubscribe(&self, key: K) -> Result<(), Cancelled> {
-        self.send(Message::Unsubscribe(key))
+    fn eq(&self, other: &Self) -> bool {
+        self.order() == other.order()
     }
+}
 
+impl<K, T> Eq for Prioritized<K, T> {}
+
+impl<K, T> PartialOrd for Prioritized<K, T> {
     #[inline(always)]
-    ///Sends message `msg` over to channel registered by `key`.
-    ///
-    ///Returns `Ok(())` if registry is still running
-    ///Returns `Err(Cancelled)` if message ignored due to registry not running
-    pub fn send_to(&self, key: K, msg: T) -> Result<(), Cancelled> {
-        self.send(Message::Msg(key, msg))
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>> Clone for Channel<K, T, S> {
+impl<K, T> Ord for Prioritized<K, T> {
     #[inline(always)]
-    fn clone(&self) -> Self {
-        Self {
-            state: self.state.clone(),
-            channel: self.channel.clone(),
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.order().cmp(&other.order())
+    }
+}
+
+//Messages, buffered to be delivered in order of priority
+struct Priority<K, T> {
+    pending: BinaryHeap<Prioritized<K, T>>,
+    seq: u64,
+}
+
+impl<K, T> Priority<K, T> {
+    #[inline(always)]
+    fn push(&mut self, key: K, message: T, priority: u8) {
+        self.seq = self.seq.wrapping_add(1);
+        self.pending.push(Prioritized {
+            priority,
+            seq: self.seq,
+            key,
+            message,
+        });
+    }
+}
+
+//Messages, buffered to be delivered newest first
+enum Lifo<K, T> {
+    All(Vec<(K, T)>),
+    //Latest message per key with its sequence number, and last sequence number
+    Latest(HashMap<K, (u64, T)>, u64),
+}
+
+impl<K: PartialEq + Eq + Hash, T> Lifo<K, T> {
+    #[inline(always)]
+    fn push(&mut self, key: K, message: T) {
+        match self {
+            Lifo::All(pending) => pending.push((key, message)),
+            Lifo::Latest(pending, seq) => {
+                *seq = seq.wrapping_add(1);
+                pending.insert(key, (*seq, message));
+            },
+        }
+    }
+
+    //Takes buffered messages, newest first
+    fn take(&mut self) -> Vec<(K, T)> {
+        match self {
+            Lifo::All(pending) => {
+                let mut pending = core::mem::take(pending);
+                pending.reverse();
+                pending
+            },
+            Lifo::Latest(pending, _) => {
+                let mut pending = pending.drain().collect::<Vec<_>>();
+                pending.sort_unstable_by(|(_, (left, _)), (_, (right, _))| right.cmp(left));
+                pending.into_iter().map(|(key, (_, message))| (key, message)).collect()
+            },
         }
     }
 }
 
-impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>> Drop for Channel<K, T, S> {
+//Periodic hand over of subscriber keys
+struct Checkpoint<K> {
+    interval: time::Duration,
+    //Moment of next checkpoint, `None` if it is too far to be ever reached
+    next: Option<std::time::Instant>,
+    hook: CheckpointHook<K>,
+}
+
+//Messages, rejected by full subscribers, waiting to be retried
+struct Inflight<K, T> {
+    limit: usize,
+    clone_key: fn(&K) -> K,
+    pending: HashMap<K, VecDeque<T>>,
+}
+
+struct Hooks<K> {
+    on_evict: Option<EvictHook<K>>,
+    events: Option<EventHook<K>>,
+    on_empty: Option<EmptyHook>,
+    on_nonempty: Option<EmptyHook>,
+    //Whether registry had no keyed subscriber, when last checked
+    empty: bool,
+}
+
+impl<K> Hooks<K> {
     #[inline(always)]
-    fn drop(&mut self) {
-        //Drop channel pipe first, to ensure it gets broken on receiver task
-        unsafe {
-            ManuallyDrop::drop(&mut self.channel)
+    fn emit(&mut self, event: RegistryEvent<&K>) {
+        if let Some(events) = self.events.as_mut() {
+            if !events(event) {
+                self.events = None;
+            }
         }
+    }
 
-        //There is always only 1 receiver therefore count of 2 means it is last sender
-        //Drop order doesn't really matter for senders as long as we wake task
-        if Arc::strong_count(&self.state) <= 2 {
-            //If it is last sender
-            //In order to terminate task
-            //Wake it up, if it is still listening
-            self.state.waker.wake();
+    //Invokes hook, if registry became empty or non-empty since last check
+    #[inline(always)]
+    fn check_empty(&mut self, empty: bool) {
+        if self.empty != empty {
+            self.empty = empty;
+            let hook = match empty {
+                true => self.on_empty.as_mut(),
+                false => self.on_nonempty.as_mut(),
+            };
+            if let Some(hook) = hook {
+                hook();
+            }
         }
     }
 }
+#[cfg(any(feature = "log", feature = "tracing"))]
+type KeyFmt<K> = fn(&K, &mut fmt::Formatter<'_>) -> fmt::Result;
 
-///Creates new registry returning sending channel and registry task
-pub fn registry<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>>() -> (Channel<K, T, S>, Registry<K, T, S>) {
-    let (channel, recv) = mpsc::channel();
-    let state = Arc::new(State::new());
-    let chan = Channel {
-        channel: ManuallyDrop::new(channel),
-        state: state.clone(),
-    };
-    (chan, Registry::new(state, recv))
+#[cfg(any(feature = "log", feature = "tracing"))]
+struct LogKey<'a, K>(&'a K, KeyFmt<K>);
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+impl<K> fmt::Display for LogKey<'_, K> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.1)(self.0, fmt)
+    }
+}
+
+#[inline(always)]
+fn evict<K>(hooks: &mut Hooks<K>, key: &K) {
+    if let Some(on_evict) = hooks.on_evict.as_mut() {
+        on_evict(key);
+    }
+    hooks.emit(RegistryEvent::Evicted(key));
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+///Unique identifier of registry within process.
+///
+///Allows to check whether channels belong to the same registry.
+pub struct RegistryId(u64);
+
+impl RegistryId {
+    #[inline(always)]
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    #[inline(always)]
+    ///Returns raw value of identifier
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+struct State {
+    id: RegistryId,
+    waker: waker::AtomicWaker,
+    //Number of alive `Channel` instances
+    senders: AtomicUsize,
+    //Number of keyed subscribers, as last published by registry
+    subscribers: AtomicUsize,
+    //Number of messages accepted by subscribers
+    delivered: AtomicU64,
+    //Number of messages dropped due to expired TTL
+    expired: AtomicU64,
+    //Number of messages discarded due to overflow of bounded queue
+    overflowed: AtomicU64,
+    //Number of discarded messages, that never reach dead letter sink, as too many of them are waiting
+    overflow_lost: AtomicU64,
+    //Number of messages queued, but not yet handled by registry
+    pending: AtomicUsize,
+    ready: ready::Readiness,
+    //Whether registry finished processing, which is never cancelled
+    finished: ready::Readiness,
+    //Whether `Registry` is dropped
+    closed: AtomicBool,
+    //Whether `Registry` is requested to stop
+    stopped: AtomicBool,
+    //Whether `Registry` is draining its queue, so that senders don't need to wake it
+    running: AtomicBool,
+    //Whether `Registry` ever panicked while processing messages
+    panicked: AtomicBool,
+    //Whether `StopHandle` was ever created, so that registry can be stopped from elsewhere
+    #[cfg(feature = "blocking")]
+    stoppable: AtomicBool,
+    //Number of alive `WeakChannel` instances, which keep queue open
+    #[cfg(feature = "blocking")]
+    weak: AtomicUsize,
+    //Thread, which is currently processing messages
+    #[cfg(debug_assertions)]
+    worker: std::sync::Mutex<Option<std::thread::ThreadId>>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            id: RegistryId::next(),
+            waker: waker::AtomicWaker::new(),
+            senders: AtomicUsize::new(1),
+            subscribers: AtomicUsize::new(0),
+            delivered: AtomicU64::new(0),
+            expired: AtomicU64::new(0),
+            overflowed: AtomicU64::new(0),
+            overflow_lost: AtomicU64::new(0),
+            pending: AtomicUsize::new(0),
+            ready: ready::Readiness::new(),
+            finished: ready::Readiness::new(),
+            closed: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            running: AtomicBool::new(false),
+            panicked: AtomicBool::new(false),
+            #[cfg(feature = "blocking")]
+            stoppable: AtomicBool::new(false),
+            #[cfg(feature = "blocking")]
+            weak: AtomicUsize::new(0),
+            #[cfg(debug_assertions)]
+            worker: std::sync::Mutex::new(None),
+        }
+    }
+
+    #[inline(always)]
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+        self.waker.wake();
+    }
+
+    #[inline(always)]
+    fn on_delivered(&self, count: usize) {
+        self.delivered.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    //Blocking on reply within registry thread would never finish as registry waits for us
+    fn assert_not_worker(&self) {
+        #[cfg(debug_assertions)]
+        if let Ok(worker) = self.worker.lock() {
+            debug_assert_ne!(*worker, Some(std::thread::current().id()), "Blocking request from within registry thread would deadlock");
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+//Marks current thread as registry worker, until guard is dropped
+struct WorkerGuard(Arc<State>);
+
+#[cfg(debug_assertions)]
+impl WorkerGuard {
+    fn enter(state: Arc<State>) -> Self {
+        if let Ok(mut worker) = state.worker.lock() {
+            *worker = Some(std::thread::current().id());
+        }
+        Self(state)
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Drop for WorkerGuard {
+    fn drop(&mut self) {
+        if let Ok(mut worker) = self.0.worker.lock() {
+            *worker = None;
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+///Describes why registry is no longer usable
+pub enum CancelReason {
+    ///Registry is not running, hence message cannot be processed
+    NotRunning,
+    ///All `Channel` instances are dropped, therefore registry has nothing to process
+    Disconnected,
+    ///Registry is finished by its owner
+    Finished,
+    ///Registry started processing request, but is gone before replying
+    ReplyLost,
+}
+
+impl CancelReason {
+    #[inline(always)]
+    const fn as_str(&self) -> &'static str {
+        match self {
+            CancelReason::NotRunning => "registry is not running",
+            CancelReason::Disconnected => "all channels are dropped",
+            CancelReason::Finished => "registry is finished",
+            CancelReason::ReplyLost => "registry is gone before replying",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+///Indicates remote end has been dropped, making this end unusable
+pub struct Cancelled {
+    reason: CancelReason,
+}
+
+impl Cancelled {
+    pub(crate) const NOT_RUNNING: Self = Self::new(CancelReason::NotRunning);
+    pub(crate) const DISCONNECTED: Self = Self::new(CancelReason::Disconnected);
+    pub(crate) const FINISHED: Self = Self::new(CancelReason::Finished);
+    pub(crate) const REPLY_LOST: Self = Self::new(CancelReason::ReplyLost);
+
+    #[inline(always)]
+    pub(crate) const fn new(reason: CancelReason) -> Self {
+        Self {
+            reason,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns reason of cancellation
+    pub const fn reason(&self) -> CancelReason {
+        self.reason
+    }
+}
+
+impl fmt::Debug for Cancelled {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, fmt)
+    }
+}
+
+impl fmt::Display for Cancelled {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("Cancelled: ")?;
+        fmt.write_str(self.reason.as_str())
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+///Describes failure of request, awaiting reply from registry (e.g. `Channel::try_subscribe`)
+///
+///Created from `Cancelled`, returned by such requests, to tell whether request could take effect.
+pub enum RequestError {
+    ///Registry never processed request, hence it is safe to retry
+    RegistryGone,
+    ///Registry started processing request, but is gone before replying, hence request may be applied
+    ReplyLost,
+}
+
+impl From<Cancelled> for RequestError {
+    #[inline(always)]
+    fn from(error: Cancelled) -> Self {
+        match error.reason {
+            CancelReason::ReplyLost => RequestError::ReplyLost,
+            _ => RequestError::RegistryGone,
+        }
+    }
+}
+
+impl fmt::Display for RequestError {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(match self {
+            RequestError::RegistryGone => "registry never processed request",
+            RequestError::ReplyLost => CancelReason::ReplyLost.as_str(),
+        })
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+///Describes why message could not be delivered to subscriber
+pub enum SubscriberGone {
+    ///There is no subscriber of the key
+    NoSubscriber,
+    ///Subscriber rejected message as closed, and is removed
+    Closed,
+    ///Registry is not running, hence message cannot be processed
+    NotRunning,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+///Outcome of `Channel::send_to_if_present`
+pub enum SendOutcome<T> {
+    ///Subscriber accepted message
+    Delivered,
+    ///Subscriber failed to accept message, which is passed to dead letter sink, if any
+    Rejected,
+    ///There is no subscriber of the key, hence message is returned back
+    Vacant(T),
+}
+
+impl fmt::Display for SubscriberGone {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(match self {
+            SubscriberGone::NoSubscriber => "no subscriber",
+            SubscriberGone::Closed => "subscriber is closed",
+            SubscriberGone::NotRunning => CancelReason::NotRunning.as_str(),
+        })
+    }
+}
+
+impl std::error::Error for SubscriberGone {}
+
+#[cfg(feature = "metrics")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+///Statistics of waker registrations, as returned by `Channel::waker_stats`
+pub struct WakerStats {
+    ///Number of registrations, that cloned new waker
+    pub cloned: u64,
+    ///Number of registrations, that kept already registered waker, as it would wake the same task
+    pub reused: u64,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+///Totals of operations handled by `Registry`
+pub struct RunSummary {
+    ///Number of channels subscribed, including broadcast, round-robin groups and shards
+    pub subscribed: u64,
+    ///Number of channels unsubscribed, excluding those removed due to being closed
+    pub unsubscribed: u64,
+    ///Number of messages accepted by subscribers, as `Channel::delivered_count`
+    pub delivered: u64,
+    ///Number of messages sent to key, that no subscriber accepted, including messages passed to dead letter sink
+    pub dropped: u64,
+}
+
+#[derive(Clone)]
+///Handle to request `Registry` to stop.
+pub struct StopHandle {
+    state: Arc<State>,
+}
+
+impl StopHandle {
+    #[inline(always)]
+    ///Requests registry to stop, as `Registry::close`
+    pub fn stop(&self) {
+        self.state.stop();
+    }
+}
+
+impl fmt::Debug for StopHandle {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("StopHandle")
+           .field("stopped", &self.state.stopped.load(Ordering::Relaxed))
+           .finish()
+    }
+}
+
+#[must_use = "You must run Registry task"]
+///Task to manage messages within Registry
+///
+///It is expected running as either async task or on dedicated thread worker.
+///
+///This future is never ending, unless Registry gets dropped, resulting in error.
+///
+///When spawned as task (e.g. `smol::spawn(registry)`), channels wake that task directly, so no
+///dedicated thread is needed. Refer to `examples/smol.rs`.
+///
+///Registry is `Send` as long as `K`, `T` and `S` are `Send`, so it can be created on one thread and
+///moved to worker thread.
+///
+///Registry is always `Unpin`, so it can be polled via `Pin::new` even if `K`, `T` or `S` is not.
+///
+///Parameters are not required to be `'static`, so registry can hold borrowed channels (e.g. `&S`)
+///when running within `std::thread::scope`, as long as they are `Send` to move registry to worker.
+pub struct Registry<K: PartialEq + Eq, T: Send, S: Sender<T>, M = ()> {
+    state: Arc<State>,
+    registry: HashMap<K, Subscriber<S, M>>,
+    broadcast: HashMap<K, Vec<S>>,
+    //Round-robin groups by key
+    groups: HashMap<K, Group<S>>,
+    //Shard subscribers by number of shards, then by shard
+    shards: BTreeMap<u32, HashMap<u32, S>>,
+    dead_letter: Option<S>,
+    //Subscriber of every delivered message
+    tap: Option<(S, fn(&T) -> T)>,
+    //Transformation of messages to keyed subscribers
+    middleware: Option<Middleware<K, T>>,
+    //Creates subscriber of vacant key on demand
+    factory: Option<(SubscriberFactory<K, S>, fn(&K) -> K)>,
+    hooks: Hooks<K>,
+    summary: RunSummary,
+    poll_budget: usize,
+    max_subscribers: usize,
+    max_keys_per_sender: usize,
+    //Latest pending message per key, if coalescing is enabled
+    coalesce: Option<HashMap<K, T>>,
+    //Messages pending in order of arrival, to be delivered newest first, if LIFO is enabled
+    lifo: Option<Lifo<K, T>>,
+    //Messages pending in order of priority, if prioritization is enabled
+    priority: Option<Priority<K, T>>,
+    //Buffered messages of full subscribers, if enabled
+    inflight: Option<Inflight<K, T>>,
+    checkpoint: Option<Checkpoint<K>>,
+    #[cfg(feature = "log")]
+    key_fmt: Option<KeyFmt<K>>,
+    #[cfg(feature = "tracing")]
+    trace_key: Option<KeyFmt<K>>,
+    recv: queue::Rx<K, T, S, M>,
+    //Lane of control operations, if they are prioritized
+    control: Option<mpsc::Receiver<Message<K, T, S, M>>>,
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Registry<K, T, S, M> {
+
+    ///Process registry messages until cancelled.
+    ///
+    ///This function blocks, until all sending channels gets closed
+    ///
+    ///With `blocking` feature, it blocks on receiving from queue whenever possible, instead of parking thread.
+    pub fn run(&mut self) -> Cancelled {
+        #[cfg(feature = "blocking")]
+        if let Some(error) = self.run_blocking() {
+            return error;
+        }
+
+        let waker = waker::thread::waker(std::thread::current());
+
+        loop {
+            match self.process(&waker) {
+                task::Poll::Ready(error) => break error,
+                task::Poll::Pending => match self.checkpoint.as_ref().and_then(|checkpoint| checkpoint.next) {
+                    //Wake up in time for checkpoint, even if there are no messages
+                    Some(next) => std::thread::park_timeout(next.saturating_duration_since(std::time::Instant::now())),
+                    None => std::thread::park(),
+                },
+            }
+        }
+    }
+
+    ///Process registry messages until cancelled, restarting processing if it panics up to `restarts` times.
+    ///
+    ///Registry keeps its subscribers across restarts, so channels continue to work after panic,
+    ///while operation, which caused panic, is lost. Panic is reported via `Channel::worker_panicked`.
+    ///Once `restarts` are exhausted, panic is propagated to caller.
+    ///
+    ///Registry state is assumed to be unwind safe: subscriber, which panicked, stays subscribed
+    ///unless it is removed by later failure, so consider `panic-safe` feature to evict it instead.
+    pub fn run_supervised(&mut self, mut restarts: usize) -> Cancelled {
+        loop {
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.run())) {
+                Ok(reason) => break reason,
+                Err(panic) => {
+                    self.state.panicked.store(true, Ordering::Release);
+                    if restarts == 0 {
+                        std::panic::resume_unwind(panic);
+                    }
+                    restarts -= 1;
+                },
+            }
+        }
+    }
+
+    #[inline(always)]
+    ///Processes all queued messages, arranging `waker` to be woken once there are new ones.
+    ///
+    ///This is the same as polling registry as `Future`, but without `Context`, allowing to drive
+    ///registry from event loop, that is notified via waker created by `notify_waker`.
+    ///
+    ///Returns `Poll::Ready(Cancelled)` once registry is finished, after which it should not be driven anymore.
+    pub fn poll_with(&mut self, waker: &task::Waker) -> task::Poll<Cancelled> {
+        self.process(waker)
+    }
+
+    #[inline(always)]
+    ///Returns identifier of registry, which is shared by its channels.
+    pub fn id(&self) -> RegistryId {
+        self.state.id
+    }
+
+    #[inline(always)]
+    ///Returns number of keyed subscribers.
+    ///
+    ///Broadcast, round-robin groups and shards are not included. As registry is not shared,
+    ///this is exact number as of last poll, unlike `Channel::subscriber_count_hint`.
+    pub fn len(&self) -> usize {
+        self.registry.len()
+    }
+
+    #[inline(always)]
+    ///Returns whether there are no keyed subscribers.
+    pub fn is_empty(&self) -> bool {
+        self.registry.is_empty()
+    }
+
+    #[inline]
+    ///Returns totals of operations, handled by registry so far.
+    pub fn summary(&self) -> RunSummary {
+        RunSummary {
+            delivered: self.state.delivered.load(Ordering::Relaxed),
+            ..self.summary
+        }
+    }
+
+    #[inline(always)]
+    ///Requests registry to stop, even if there are still channels.
+    ///
+    ///Once requested, registry stops on next iteration, resolving with `Finished` reason, and
+    ///channels can no longer send messages. Use `finish` to retrieve subscribers afterwards.
+    pub fn close(&self) {
+        self.state.stop();
+    }
+
+    #[inline(always)]
+    ///Creates handle, allowing to request registry to stop from elsewhere (e.g. while it is running on worker thread).
+    ///
+    ///Refer to `close` for details.
+    pub fn stop_handle(&self) -> StopHandle {
+        #[cfg(feature = "blocking")]
+        self.state.stoppable.store(true, Ordering::Relaxed);
+        StopHandle {
+            state: self.state.clone(),
+        }
+    }
+
+    ///Stops registry, returning its subscribers.
+    ///
+    ///All messages, that are already queued, are processed before stopping.
+    ///Broadcast groups are dropped, as only direct subscribers are returned.
+    ///
+    ///Returned subscribers can be used to start new registry via `registry_from_map`.
+    ///Reason is `Disconnected` if all channels are dropped, otherwise it is `Finished`, in which
+    ///case remaining channels can no longer send messages.
+    pub fn finish(mut self) -> (HashMap<K, S>, Cancelled) {
+        let reason = loop {
+            match self.recv_next() {
+                Ok(message) => self.handle(message),
+                Err(mpsc::TryRecvError::Disconnected) => break Cancelled::DISCONNECTED,
+                Err(mpsc::TryRecvError::Empty) => break Cancelled::FINISHED,
+            }
+        };
+        self.flush();
+        self.dead_letter_dropped();
+        //Subscribers are returned, so there is no one to retry buffered messages
+        if let Some(inflight) = self.inflight.take() {
+            for message in inflight.pending.into_values().flatten() {
+                self.summary.dropped += 1;
+                self.dead_letter(message);
+            }
+        }
+
+        let registry = self.registry.drain().map(|(key, subscriber)| (key, subscriber.channel)).collect();
+        (registry, reason)
+    }
+
+    //Returns whether there is shard subscriber for `key`
+    fn has_shard(&self, key: &K) -> bool {
+        if self.shards.is_empty() {
+            return false;
+        }
+
+        let hash = shard_hash(key);
+        self.shards.iter().any(|(of, shards)| shards.contains_key(&((hash % u64::from(*of)) as u32)))
+    }
+
+    //Receives next message, taking control operations first, if they are prioritized
+    fn recv_next(&self) -> Result<Message<K, T, S, M>, mpsc::TryRecvError> {
+        if let Some(control) = self.control.as_ref() {
+            if let Ok(message) = control.try_recv() {
+                return Ok(message);
+            }
+        }
+
+        match self.recv.try_recv(&self.state) {
+            //Control operation might be sent right before last channel is dropped
+            Err(mpsc::TryRecvError::Disconnected) => match self.control.as_ref().and_then(|control| control.try_recv().ok()) {
+                Some(message) => Ok(message),
+                None => Err(mpsc::TryRecvError::Disconnected),
+            },
+            result => result,
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    //Processes messages, blocking on queue while there are none
+    //
+    //Returns `None` without processing anything, if registry may need to be woken by something
+    //other than message.
+    fn run_blocking(&mut self) -> Option<Cancelled> {
+        if !self.recv.is_unbounded() || self.control.is_some() || self.checkpoint.is_some() || self.state.stoppable.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        self.state.ready.start();
+        #[cfg(debug_assertions)]
+        let _guard = WorkerGuard::enter(self.state.clone());
+        //Senders never need to wake registry as it is woken by message itself
+        self.state.running.store(true, Ordering::Relaxed);
+        let result = loop {
+            if self.state.stopped.load(Ordering::Acquire) {
+                break Cancelled::FINISHED;
+            }
+
+            let message = match self.recv.try_recv(&self.state) {
+                Ok(message) => message,
+                Err(mpsc::TryRecvError::Empty) => {
+                    self.flush();
+                    match self.recv.recv() {
+                        Ok(message) => message,
+                        Err(mpsc::RecvError) => break Cancelled::DISCONNECTED,
+                    }
+                },
+                Err(mpsc::TryRecvError::Disconnected) => break Cancelled::DISCONNECTED,
+            };
+            self.handle(message);
+        };
+        self.state.running.store(false, Ordering::Relaxed);
+        self.flush();
+        self.state.finished.start();
+        Some(result)
+    }
+
+    //Hands over keys to checkpoint hook, if interval elapsed
+    fn checkpoint(&mut self) {
+        if let Some(checkpoint) = self.checkpoint.as_mut() {
+            let now = std::time::Instant::now();
+            if checkpoint.next.map_or(false, |next| now >= next) {
+                checkpoint.next = now.checked_add(checkpoint.interval);
+                let keys = self.registry.keys().collect::<Vec<_>>();
+                (checkpoint.hook)(&keys);
+            }
+        }
+    }
+
+    //Receives next message, marking registry as idle, to be woken via `waker`, if there is none
+    fn recv_or_idle(&mut self, waker: &task::Waker) -> Result<Message<K, T, S, M>, mpsc::TryRecvError> {
+        match self.recv_next() {
+            Err(mpsc::TryRecvError::Empty) => {
+                self.flush();
+                //Waker must be in place before queue is checked for the last time, otherwise
+                //sender could wake previous waker, which is already taken by former wake up.
+                self.state.waker.register_ref(waker);
+                self.state.running.store(false, Ordering::Relaxed);
+                //Pairs with fence in `Channel::send_raw`: either sender observes registry as idle
+                //and wakes it, or its message is visible to this check.
+                atomic::fence(Ordering::SeqCst);
+                let result = self.recv_next();
+                if result.is_ok() {
+                    self.state.running.store(true, Ordering::Relaxed);
+                }
+                result
+            },
+            result => result,
+        }
+    }
+
+    //Delivers message to subscriber of `key`, returning message back if there is no alive subscriber
+    fn try_deliver(&mut self, key: K, message: T) -> Result<(), Undelivered<K, T>> {
+        match self.registry.entry(key) {
+            hash_map::Entry::Occupied(entry) => {
+                let message = match self.middleware.as_mut() {
+                    Some(middleware) => match middleware(entry.key(), message) {
+                        Some(message) => message,
+                        //Filtered out message is lost as if subscriber failed, but subscriber is kept
+                        None => return Err(Undelivered::Lost),
+                    },
+                    None => message,
+                };
+
+                //Messages, buffered for busy subscriber, go first to keep order
+                if let Some(inflight) = self.inflight.as_mut() {
+                    if let Some(buffer) = inflight.pending.get_mut(entry.key()) {
+                        let mut alive = drain_buffer(&entry.get().channel, buffer, &self.state, &mut self.tap);
+                        let mut result = match alive && buffer.len() < inflight.limit {
+                            true => {
+                                buffer.push_back(message);
+                                alive = drain_buffer(&entry.get().channel, buffer, &self.state, &mut self.tap);
+                                Ok(())
+                            },
+                            false => Err(Undelivered::Rejected(message)),
+                        };
+                        if !alive {
+                            let (key, _) = entry.remove_entry();
+                            let mut undelivered = inflight.pending.remove(&key).unwrap_or_default();
+                            if result.is_ok() {
+                                //Message is last in buffer, unless it is lost to failed subscriber
+                                result = Err(match undelivered.pop_back() {
+                                    Some(message) => Undelivered::Rejected(message),
+                                    None => Undelivered::Lost,
+                                });
+                            }
+                            evict(&mut self.hooks, &key);
+                            self.summary.dropped += undelivered.len() as u64;
+                            for message in undelivered {
+                                self.dead_letter(message);
+                            }
+                        } else if buffer.is_empty() {
+                            inflight.pending.remove(entry.key());
+                        }
+                        return result;
+                    }
+                }
+
+                match (tap_copy(&self.tap, &message), deliver(&entry.get().channel, message)) {
+                    (copy, Ok(())) => {
+                        self.state.on_delivered(1);
+                        tap(&mut self.tap, copy);
+                        Ok(())
+                    },
+                    (_, Err(None)) => {
+                        let (key, _) = entry.remove_entry();
+                        evict(&mut self.hooks, &key);
+                        Err(Undelivered::Lost)
+                    },
+                    (_, Err(Some(error))) => match error.kind {
+                        SendErrorKind::Closed => {
+                            let (key, _) = entry.remove_entry();
+                            evict(&mut self.hooks, &key);
+                            Err(Undelivered::Rejected(error.message))
+                        },
+                        SendErrorKind::Full => match self.inflight.as_mut() {
+                            Some(inflight) if inflight.limit > 0 => {
+                                let mut buffer = VecDeque::new();
+                                buffer.push_back(error.message);
+                                inflight.pending.insert((inflight.clone_key)(entry.key()), buffer);
+                                Ok(())
+                            },
+                            _ => Err(Undelivered::Rejected(error.message)),
+                        },
+                    }
+                }
+            },
+            hash_map::Entry::Vacant(entry) => {
+                let entry = match self.groups.entry(entry.into_key()) {
+                    hash_map::Entry::Occupied(mut group) => {
+                        let copy = tap_copy(&self.tap, &message);
+                        let (result, evicted) = deliver_group(group.get_mut(), message);
+                        for _ in 0..evicted {
+                            evict(&mut self.hooks, group.key());
+                        }
+                        if group.get().channels.is_empty() {
+                            group.remove();
+                        }
+                        return match result {
+                            Ok(()) => {
+                                self.state.on_delivered(1);
+                                tap(&mut self.tap, copy);
+                                Ok(())
+                            },
+                            Err(None) => Err(Undelivered::Lost),
+                            Err(Some(message)) => Err(Undelivered::Rejected(message)),
+                        };
+                    },
+                    hash_map::Entry::Vacant(entry) => entry,
+                };
+
+                let hash = shard_hash(entry.key());
+                for (of, shards) in self.shards.iter_mut() {
+                    if let hash_map::Entry::Occupied(shard) = shards.entry((hash % u64::from(*of)) as u32) {
+                        return match (tap_copy(&self.tap, &message), deliver(shard.get(), message)) {
+                            (copy, Ok(())) => {
+                                self.state.on_delivered(1);
+                                tap(&mut self.tap, copy);
+                                Ok(())
+                            },
+                            (_, Err(None)) => {
+                                shard.remove();
+                                Err(Undelivered::Lost)
+                            },
+                            (_, Err(Some(error))) => {
+                                if error.kind.is_closed() {
+                                    shard.remove();
+                                }
+                                Err(Undelivered::Rejected(error.message))
+                            },
+                        };
+                    }
+                }
+
+                let key = entry.into_key();
+                if let Some((factory, clone_key)) = self.factory.as_mut() {
+                    let clone_key = *clone_key;
+                    if let Some(channel) = factory(&key) {
+                        let subscriber = Subscriber {
+                            id: None,
+                            channel,
+                            meta: None,
+                        };
+                        if self.subscribe(clone_key(&key), subscriber) {
+                            return self.try_deliver(key, message);
+                        }
+                    }
+                }
+
+                Err(Undelivered::Unknown(key, message))
+            },
+        }
+    }
+
+    //Delivers messages, buffered for busy subscribers, as long as they accept them
+    //
+    //Messages of key, that has no subscriber anymore, are treated as undelivered.
+    fn retry_inflight(&mut self) {
+        let mut inflight = match self.inflight.take() {
+            Some(inflight) => inflight,
+            None => return,
+        };
+
+        let mut undelivered = Vec::new();
+        inflight.pending.retain(|key, buffer| {
+            let alive = match self.registry.get(key) {
+                Some(subscriber) => drain_buffer(&subscriber.channel, buffer, &self.state, &mut self.tap),
+                None => false,
+            };
+            if !alive {
+                if self.registry.remove(key).is_some() {
+                    evict(&mut self.hooks, key);
+                }
+                undelivered.extend(buffer.drain(..));
+            }
+            !buffer.is_empty()
+        });
+        self.inflight = Some(inflight);
+
+        self.summary.dropped += undelivered.len() as u64;
+        for message in undelivered {
+            self.dead_letter(message);
+        }
+    }
+
+    //Passes undelivered message to dead letter sink, if any
+    fn dead_letter(&mut self, message: T) {
+        if let Some(dead_letter) = self.dead_letter.as_ref() {
+            if !is_alive(deliver(dead_letter, message)) {
+                self.dead_letter = None;
+                self.recv.discard_dropped();
+            }
+        }
+    }
+
+    //Passes messages, discarded due to overflow of bounded queue, to dead letter sink
+    fn dead_letter_dropped(&mut self) {
+        for message in self.recv.take_dropped() {
+            self.summary.dropped += 1;
+            self.dead_letter(message);
+        }
+    }
+
+    //Delivers message to subscriber of `key`, returning whether it is accepted
+    //
+    //Message, which cannot be delivered, is passed to dead letter sink.
+    fn deliver(&mut self, key: K, message: T) -> bool {
+        match self.try_deliver(key, message) {
+            Ok(()) => true,
+            Err(Undelivered::Lost) => {
+                self.summary.dropped += 1;
+                false
+            },
+            Err(Undelivered::Rejected(message)) => {
+                self.summary.dropped += 1;
+                self.dead_letter(message);
+                false
+            },
+            Err(Undelivered::Unknown(_key, message)) => {
+                #[cfg(feature = "log")]
+                if self.dead_letter.is_none() {
+                    match self.key_fmt {
+                        Some(key_fmt) => log::debug!("pochta: dropped message for unknown key={}", LogKey(&_key, key_fmt)),
+                        None => log::debug!("pochta: dropped message for unknown key"),
+                    }
+                }
+                self.summary.dropped += 1;
+                self.dead_letter(message);
+                false
+            },
+        }
+    }
+
+    //Delivers clone of message to every key, moving it into the last one
+    fn deliver_all(&mut self, mut keys: Vec<K>, message: T, clone: fn(&T) -> T) {
+        if let Some(last) = keys.pop() {
+            for key in keys {
+                self.deliver(key, clone(&message));
+            }
+            self.deliver(last, message);
+        }
+    }
+
+    //Returns whether subscribing `key` on behalf of `id` exceeds limit of keys per subscriber
+    fn exceeds_keys_per_sender(&self, key: &K, id: Option<SubscriberId>) -> bool {
+        match id {
+            Some(id) if self.max_keys_per_sender != usize::MAX => {
+                let subscribed = self.registry.values().filter(|subscriber| subscriber.id == Some(id)).count();
+                subscribed >= self.max_keys_per_sender && self.registry.get(key).map_or(true, |subscriber| subscriber.id != Some(id))
+            },
+            _ => false,
+        }
+    }
+
+    //Registers subscriber of `key`, returning whether it is accepted
+    fn subscribe(&mut self, key: K, subscriber: Subscriber<S, M>) -> bool {
+        if subscriber.channel.is_closed() {
+            //Closed channel still replaces existing one, as if it was pruned right away
+            if self.registry.remove(&key).is_some() {
+                self.hooks.emit(RegistryEvent::Unsubscribed(&key));
+            }
+            false
+        } else if self.registry.len() >= self.max_subscribers && !self.registry.contains_key(&key) {
+            false
+        } else if self.exceeds_keys_per_sender(&key, subscriber.id) {
+            #[cfg(feature = "log")]
+            log::warn!("pochta: rejected subscription of {:?} exceeding limit of {} keys", subscriber.id, self.max_keys_per_sender);
+            false
+        } else {
+            self.summary.subscribed += 1;
+            self.hooks.emit(RegistryEvent::Subscribed(&key));
+            self.registry.insert(key, subscriber);
+            true
+        }
+    }
+
+    //Publishes number of keyed subscribers, invoking hooks if registry became empty or non-empty
+    #[inline(always)]
+    fn update_subscribers(&mut self) {
+        let subscribers = self.registry.len();
+        self.state.subscribers.store(subscribers, Ordering::Relaxed);
+        self.hooks.check_empty(subscribers == 0);
+    }
+
+    //Delivers messages, pending due to prioritization or coalescing
+    fn flush(&mut self) {
+        if let Some(mut priority) = self.priority.take() {
+            while let Some(pending) = priority.pending.pop() {
+                self.deliver(pending.key, pending.message);
+            }
+            self.priority = Some(priority);
+        }
+        if let Some(mut pending) = self.coalesce.take() {
+            for (key, message) in pending.drain() {
+                self.deliver(key, message);
+            }
+            self.coalesce = Some(pending);
+        }
+        if let Some(mut lifo) = self.lifo.take() {
+            for (key, message) in lifo.take() {
+                self.deliver(key, message);
+            }
+            self.lifo = Some(lifo);
+        }
+        self.retry_inflight();
+        self.update_subscribers();
+    }
+
+    fn handle(&mut self, message: Message<K, T, S, M>) {
+        self.state.pending.fetch_sub(1, Ordering::Relaxed);
+
+        //Pending messages must be delivered before any other operation takes effect
+        if !matches!(message, Message::Msg(..) | Message::MsgPrio(..)) {
+            self.flush();
+        }
+
+        #[cfg(feature = "tracing")]
+        let _span = {
+            let (operation, key) = message.operation();
+            let span = tracing::debug_span!("pochta", operation, key = tracing::field::Empty);
+            if let (Some(key), Some(trace_key)) = (key, self.trace_key) {
+                span.record("key", tracing::field::display(LogKey(key, trace_key)));
+            }
+            span.entered()
+        };
+
+        //Failure from now on means request may be applied, hence its reply is marked as lost
+        message.accept();
+        match message {
+            Message::Subscribe(key, channel, reply) => {
+                let subscribed = self.subscribe(key, channel);
+                if let Some(reply) = reply {
+                    reply.send(subscribed);
+                }
+            },
+            Message::SubscribeMany(mut keys, channel, clone) => {
+                if let Some(last) = keys.pop() {
+                    for key in keys {
+                        self.subscribe(key, Subscriber {
+                            id: None,
+                            channel: clone(&channel),
+                            meta: None,
+                        });
+                    }
+                    self.subscribe(last, Subscriber {
+                        id: None,
+                        channel,
+                        meta: None,
+                    });
+                }
+            },
+            Message::SubscribeBroadcast(key, channel) => {
+                if !channel.is_closed() {
+                    self.summary.subscribed += 1;
+                    self.hooks.emit(RegistryEvent::Subscribed(&key));
+                    self.broadcast.entry(key).or_default().push(channel);
+                }
+            },
+            Message::SubscribeGroup(key, channel) => {
+                if !channel.is_closed() {
+                    self.summary.subscribed += 1;
+                    self.hooks.emit(RegistryEvent::Subscribed(&key));
+                    self.groups.entry(key).or_insert_with(|| Group {
+                        channels: Vec::new(),
+                        cursor: 0,
+                    }).channels.push(channel);
+                }
+            },
+            Message::SubscribeTap(channel, clone) => {
+                if !channel.is_closed() {
+                    self.tap = Some((channel, clone));
+                }
+            },
+            Message::IsSubscribed(id, reply) => {
+                reply.send(self.registry.values().any(|subscriber| subscriber.id == Some(id) && !subscriber.channel.is_closed()));
+            },
+            Message::UnsubscribeId(id) => {
+                let hooks = &mut self.hooks;
+                let summary = &mut self.summary;
+                self.registry.retain(|key, subscriber| {
+                    let keep = subscriber.id != Some(id);
+                    if !keep {
+                        summary.unsubscribed += 1;
+                        hooks.emit(RegistryEvent::Unsubscribed(key));
+                    }
+                    keep
+                });
+            },
+            Message::Unsubscribe(key) => {
+                let removed = self.registry.remove(&key).is_some();
+                let removed = self.groups.remove(&key).is_some() || removed;
+                if self.broadcast.remove(&key).is_some() || removed {
+                    self.summary.unsubscribed += 1;
+                    self.hooks.emit(RegistryEvent::Unsubscribed(&key));
+                }
+            },
+            Message::Rekey(from, to, reply) => {
+                let moved = match self.registry.remove(&from) {
+                    Some(subscriber) if subscriber.channel.is_closed() => {
+                        evict(&mut self.hooks, &from);
+                        false
+                    },
+                    Some(subscriber) => {
+                        self.summary.unsubscribed += 1;
+                        self.hooks.emit(RegistryEvent::Unsubscribed(&from));
+                        //Replaced channel is removed as if it is unsubscribed, unless it is closed already
+                        if let Some(replaced) = self.registry.remove(&to) {
+                            match replaced.channel.is_closed() {
+                                true => evict(&mut self.hooks, &to),
+                                false => {
+                                    self.summary.unsubscribed += 1;
+                                    self.hooks.emit(RegistryEvent::Unsubscribed(&to));
+                                },
+                            }
+                        }
+                        self.subscribe(to, subscriber)
+                    },
+                    None => false,
+                };
+                if let Some(reply) = reply {
+                    reply.send(moved);
+                }
+            },
+            Message::Msg(key, message) => match (self.coalesce.as_mut(), self.priority.as_mut(), self.lifo.as_mut()) {
+                (Some(pending), _, _) => {
+                    pending.insert(key, message);
+                },
+                (None, Some(priority), _) => priority.push(key, message, 0),
+                (None, None, Some(lifo)) => lifo.push(key, message),
+                (None, None, None) => {
+                    self.deliver(key, message);
+                },
+            },
+            Message::MsgPrio(key, message, prio) => match self.priority.as_mut() {
+                Some(priority) => priority.push(key, message, prio),
+                None => {
+                    self.deliver(key, message);
+                },
+            },
+            Message::MsgConfirm(key, message, reply) => {
+                reply.send(self.deliver(key, message));
+            },
+            Message::MsgResult(key, message, reply) => {
+                let subscribed = self.registry.contains_key(&key) || self.groups.contains_key(&key) || self.has_shard(&key);
+                reply.send(match self.deliver(key, message) {
+                    true => Ok(()),
+                    false if subscribed => Err(SubscriberGone::Closed),
+                    false => Err(SubscriberGone::NoSubscriber),
+                });
+            },
+            Message::MsgIfPresent(key, message, reply) => {
+                if self.registry.contains_key(&key) || self.groups.contains_key(&key) || self.has_shard(&key) {
+                    reply.send(match self.deliver(key, message) {
+                        true => SendOutcome::Delivered,
+                        false => SendOutcome::Rejected,
+                    });
+                } else {
+                    reply.send(SendOutcome::Vacant(message));
+                }
+            },
+            Message::MsgTtl(key, message, deadline) => match std::time::Instant::now() >= deadline {
+                true => {
+                    self.state.expired.fetch_add(1, Ordering::Relaxed);
+                    self.summary.dropped += 1;
+                    self.dead_letter(message);
+                },
+                false => {
+                    self.deliver(key, message);
+                },
+            },
+            Message::MsgWith(key, make) => {
+                if self.registry.contains_key(&key) || self.groups.contains_key(&key) || self.has_shard(&key) {
+                    self.deliver(key, make());
+                }
+            },
+            Message::MsgOr(primary, fallback, message) => {
+                match self.try_deliver(primary, message) {
+                    Err(Undelivered::Rejected(message)) | Err(Undelivered::Unknown(_, message)) => {
+                        self.deliver(fallback, message);
+                    },
+                    Ok(()) | Err(Undelivered::Lost) => (),
+                }
+            },
+            Message::Broadcast(key, message, clone, reply) => {
+                let delivered = match self.broadcast.get_mut(&key) {
+                    Some(channels) => {
+                        let delivered = broadcast(&key, channels, message, clone, &mut self.hooks);
+                        self.state.on_delivered(delivered);
+                        if channels.is_empty() {
+                            self.broadcast.remove(&key);
+                        }
+                        delivered
+                    },
+                    None => 0,
+                };
+                if let Some(reply) = reply {
+                    reply.send(delivered);
+                }
+            },
+            Message::SubscribeShard(shard, of, channel) => {
+                if !channel.is_closed() {
+                    self.summary.subscribed += 1;
+                    self.shards.entry(of).or_default().insert(shard, channel);
+                }
+            },
+            Message::UnsubscribeShard(shard, of) => {
+                if let btree_map::Entry::Occupied(mut shards) = self.shards.entry(of) {
+                    if shards.get_mut().remove(&shard).is_some() {
+                        self.summary.unsubscribed += 1;
+                    }
+                    if shards.get().is_empty() {
+                        shards.remove();
+                    }
+                }
+            },
+            Message::Compact => {
+                self.registry.shrink_to_fit();
+                self.broadcast.shrink_to_fit();
+                for channels in self.broadcast.values_mut() {
+                    channels.shrink_to_fit();
+                }
+                self.groups.shrink_to_fit();
+                for group in self.groups.values_mut() {
+                    group.channels.shrink_to_fit();
+                }
+            },
+            #[cfg(feature = "blocking")]
+            Message::Wake => (),
+            Message::PruneClosed => {
+                self.shards.retain(|_, shards| {
+                    shards.retain(|_, channel| !channel.is_closed());
+                    !shards.is_empty()
+                });
+                let on_evict = &mut self.hooks;
+                self.registry.retain(|key, subscriber| {
+                    let closed = subscriber.channel.is_closed();
+                    if closed {
+                        evict(on_evict, key);
+                    }
+                    !closed
+                });
+                self.broadcast.retain(|key, channels| {
+                    channels.retain(|channel| {
+                        let closed = channel.is_closed();
+                        if closed {
+                            evict(on_evict, key);
+                        }
+                        !closed
+                    });
+                    !channels.is_empty()
+                });
+                self.groups.retain(|key, group| {
+                    group.channels.retain(|channel| {
+                        let closed = channel.is_closed();
+                        if closed {
+                            evict(on_evict, key);
+                        }
+                        !closed
+                    });
+                    !group.channels.is_empty()
+                });
+            },
+            Message::WithKeys(with_keys, reply) => {
+                with_keys(&mut self.registry.keys());
+                reply.send(());
+            },
+            Message::WithSubscribers(with_subscribers, reply) => {
+                with_subscribers(&mut self.registry.iter().map(|(key, subscriber)| (key, &subscriber.channel, subscriber.meta.as_ref())));
+                reply.send(());
+            },
+            Message::Retain(mut retain, reply) => {
+                let hooks = &mut self.hooks;
+                let mut removed = 0;
+                self.registry.retain(|key, _| {
+                    let keep = retain(key);
+                    if !keep {
+                        removed += 1;
+                        hooks.emit(RegistryEvent::Unsubscribed(key));
+                    }
+                    keep
+                });
+                self.broadcast.retain(|key, _| {
+                    let keep = retain(key);
+                    if !keep {
+                        removed += 1;
+                        hooks.emit(RegistryEvent::Unsubscribed(key));
+                    }
+                    keep
+                });
+                self.groups.retain(|key, _| {
+                    let keep = retain(key);
+                    if !keep {
+                        removed += 1;
+                        hooks.emit(RegistryEvent::Unsubscribed(key));
+                    }
+                    keep
+                });
+                self.summary.unsubscribed += removed as u64;
+                if let Some(reply) = reply {
+                    reply.send(removed);
+                }
+            },
+            Message::MsgSelected(select, message, clone) => {
+                let keys = select(&self.registry.keys().collect::<Vec<_>>());
+                self.deliver_all(keys, message, clone);
+            },
+            Message::MsgMulti(keys, message, clone) => self.deliver_all(keys, message, clone),
+            Message::BroadcastAll(message, clone) => {
+                let on_evict = &mut self.hooks;
+                let mut delivered = 0;
+                self.registry.retain(|key, subscriber| {
+                    let result = deliver(&subscriber.channel, clone(&message));
+                    delivered += result.is_ok() as usize;
+                    let alive = is_alive(result);
+                    if !alive {
+                        evict(on_evict, key);
+                    }
+                    alive
+                });
+                self.broadcast.retain(|key, channels| {
+                    delivered += broadcast(key, channels, clone(&message), clone, on_evict);
+                    !channels.is_empty()
+                });
+                self.state.on_delivered(delivered);
+            },
+        }
+        self.update_subscribers();
+    }
+
+    fn process(&mut self, waker: &task::Waker) -> task::Poll<Cancelled> {
+        self.state.ready.start();
+        self.checkpoint();
+        self.dead_letter_dropped();
+        #[cfg(debug_assertions)]
+        let _guard = WorkerGuard::enter(self.state.clone());
+        let mut budget = self.poll_budget;
+        self.state.running.store(true, Ordering::Relaxed);
+        loop {
+            if self.finish_stopped() {
+                break task::Poll::Ready(Cancelled::FINISHED);
+            }
+
+            if budget == 0 {
+                self.state.running.store(false, Ordering::Relaxed);
+                self.flush();
+                //Yield, but make sure to be polled again as there might be more messages
+                waker.wake_by_ref();
+                break task::Poll::Pending;
+            }
+
+            match self.recv_or_idle(waker) {
+                Ok(message) => {
+                    budget -= 1;
+                    self.handle(message);
+                },
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.finish_disconnected();
+                    break task::Poll::Ready(Cancelled::DISCONNECTED);
+                },
+                Err(mpsc::TryRecvError::Empty) => break task::Poll::Pending,
+            }
+        }
+    }
+
+    //Finishes registry if stop is requested, returning whether it is finished
+    fn finish_stopped(&mut self) -> bool {
+        let stopped = self.state.stopped.load(Ordering::Acquire);
+        if stopped {
+            self.state.running.store(false, Ordering::Relaxed);
+            self.flush();
+            self.state.finished.start();
+        }
+        stopped
+    }
+
+    //Finishes registry once all channels are gone
+    fn finish_disconnected(&mut self) {
+        self.state.running.store(false, Ordering::Relaxed);
+        self.flush();
+        self.state.finished.start();
+    }
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Drop for Registry<K, T, S, M> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        //Registry is dropped by unwinding from within processing, unless panic is caught by owner
+        if std::thread::panicking() {
+            self.state.panicked.store(true, Ordering::Release);
+        }
+        self.state.closed.store(true, Ordering::Release);
+        //Channels, that outlive registry, must not wake task, that may be gone already
+        self.state.waker.clear();
+        //Resolve readiness waiters if registry is never started
+        self.state.ready.stop();
+        self.state.finished.start();
+    }
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> fmt::Debug for Registry<K, T, S, M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Registry")
+           .field("subscribers", &self.registry.len())
+           .field("broadcast_groups", &self.broadcast.len())
+           .field("pending", &self.state.pending.load(Ordering::Relaxed))
+           .finish()
+    }
+}
+
+//Registry never pins its fields, so it can be moved regardless of its parameters
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Unpin for Registry<K, T, S, M> {}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Future for Registry<K, T, S, M> {
+    type Output = Cancelled;
+
+    #[inline(always)]
+    fn poll(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let waker = ctx.waker();
+        self.get_mut().process(waker)
+    }
+}
+
+///Channel registry
+///
+///This is communication pipe towards channel
+///As long as at least one instance exist, registry task will continue running
+pub struct Channel<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M = ()> {
+    state: Arc<State>,
+    channel: ManuallyDrop<queue::Tx<K, T, S, M>>,
+    control: ManuallyDrop<Option<mpsc::Sender<Message<K, T, S, M>>>>,
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Channel<K, T, S, M> {
+    fn send(&self, msg: Message<K, T, S, M>) -> Result<(), Cancelled> {
+        match self.send_raw(msg) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Cancelled::NOT_RUNNING),
+        }
+    }
+
+    //Returns number of pending messages, including `msg`, on success
+    #[inline(always)]
+    fn send_raw(&self, msg: Message<K, T, S, M>) -> Result<usize, Message<K, T, S, M>> {
+        self.enqueue(msg, false).map_err(|(msg, _)| msg)
+    }
+
+    //Same as `send_raw`, but with option to reject message if bounded queue is full
+    fn enqueue(&self, msg: Message<K, T, S, M>, reject_full: bool) -> Result<usize, (Message<K, T, S, M>, PushErrorKind)> {
+        if self.state.stopped.load(Ordering::Acquire) {
+            return Err((msg, PushErrorKind::Cancelled));
+        }
+
+        //Increment before sending, so that registry never observes message not yet counted
+        let pending = self.state.pending.fetch_add(1, Ordering::Relaxed) + 1;
+        let result = match self.control.as_ref() {
+            Some(control) if msg.is_control() => control.send(msg).map_err(|error| (error.0, PushErrorKind::Cancelled)),
+            _ => self.channel.send(msg, reject_full, &self.state),
+        };
+        match result {
+            Ok(()) => {
+                //Registry, that is draining its queue, picks message up without being woken
+                atomic::fence(Ordering::SeqCst);
+                if !self.state.running.load(Ordering::Relaxed) {
+                    self.state.waker.wake();
+                }
+                Ok(pending)
+            },
+            Err(error) => {
+                self.state.pending.fetch_sub(1, Ordering::Relaxed);
+                Err(error)
+            }
+        }
+    }
+
+    //Sends message, returning it back if registry is not running
+    pub(crate) fn send_msg(&self, key: K, msg: T) -> Result<(), T> {
+        match self.send_raw(Message::Msg(key, msg)) {
+            Ok(_) => Ok(()),
+            Err(Message::Msg(_, msg)) => Err(msg),
+            Err(_) => unreachable!(),
+        }
+    }
+
+    #[inline(always)]
+    ///Sends operation `msg`, constructed outside of channel.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_message(&self, msg: RawMessage<K, T, S, M>) -> Result<(), Cancelled> {
+        self.send(msg.0)
+    }
+
+    #[inline]
+    ///Attempts to queue operation `msg`, without discarding other messages.
+    ///
+    ///Unlike `send_message`, if queue is limited via `RegistryBuilder::drop_oldest` and already
+    ///holds `capacity` messages, `msg` is returned back instead of discarding oldest message, which
+    ///allows caller to implement its own retry or shedding. Unlimited queue is never full.
+    ///
+    ///Returns `Ok(())` if operation is queued
+    ///Returns `Err(PushError)` with operation, if queue is full or registry is not running
+    pub fn push(&self, msg: RawMessage<K, T, S, M>) -> Result<(), PushError<K, T, S, M>> {
+        match self.enqueue(msg.0, true) {
+            Ok(_) => Ok(()),
+            Err((message, kind)) => Err(PushError {
+                kind,
+                message: RawMessage(message),
+            }),
+        }
+    }
+
+    #[inline(always)]
+    ///Subscribes provided `channel` with specified `key`, potentially removing existing channel.
+    ///
+    ///If `channel` is already closed according to `Sender::is_closed`, it is not registered, but
+    ///existing channel is still removed.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe(&self, key: K, channel: S) -> Result<(), Cancelled> {
+        self.send(Message::Subscribe(key, Subscriber {
+            id: None,
+            channel,
+            meta: None,
+        }, None))
+    }
+
+    ///Subscribes provided `channel` with specified `key`, potentially removing existing channel, replying whether it is subscribed.
+    ///
+    ///Subscription is rejected if `channel` is already closed or if registry reached limit set by
+    ///`RegistryBuilder::max_subscribers` and `key` is not yet subscribed.
+    ///
+    ///Resolves to `Ok(true)` once registry subscribed `channel`, `Ok(false)` if it is rejected.
+    ///Resolves to `Err(Cancelled)` if registry is not running
+    pub fn try_subscribe(&self, key: K, channel: S) -> impl Future<Output = Result<bool, Cancelled>> {
+        let (sender, reply) = oneshot::channel();
+        //On failure reply sender is dropped, resolving reply with `Cancelled`
+        let _ = self.send(Message::Subscribe(key, Subscriber {
+            id: None,
+            channel,
+            meta: None,
+        }, Some(sender)));
+        reply
+    }
+
+    #[inline(always)]
+    ///Subscribes provided `channel` with specified `key` on behalf of subscriber `id`, potentially removing existing channel.
+    ///
+    ///All subscriptions of `id` can be later removed using `unsubscribe_sender`
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe_as(&self, id: SubscriberId, key: K, channel: S) -> Result<(), Cancelled> {
+        self.send(Message::Subscribe(key, Subscriber {
+            id: Some(id),
+            channel,
+            meta: None,
+        }, None))
+    }
+
+    #[inline(always)]
+    ///Subscribes provided `channel` with specified `key` along with its metadata `meta`, potentially removing existing channel.
+    ///
+    ///Metadata (e.g. client address or capabilities) is kept by registry as long as channel is
+    ///subscribed and can be inspected via `with_subscribers`. Metadata type is set when registry is
+    ///created via `RegistryBuilder::with_meta` or `registry_with_meta`.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe_with_meta(&self, key: K, channel: S, meta: M) -> Result<(), Cancelled> {
+        self.send(Message::Subscribe(key, Subscriber {
+            id: None,
+            channel,
+            meta: Some(meta),
+        }, None))
+    }
+
+    ///Subscribes provided `channel` with specified `key` on behalf of subscriber `id`, replying whether it is subscribed.
+    ///
+    ///In addition to cases described in `try_subscribe`, subscription is rejected if `id` reached
+    ///limit set by `RegistryBuilder::max_keys_per_sender` and `key` is not yet subscribed by `id`.
+    ///
+    ///Resolves to `Ok(true)` once registry subscribed `channel`, `Ok(false)` if it is rejected.
+    ///Resolves to `Err(Cancelled)` if registry is not running
+    pub fn try_subscribe_as(&self, id: SubscriberId, key: K, channel: S) -> impl Future<Output = Result<bool, Cancelled>> {
+        let (sender, reply) = oneshot::channel();
+        //On failure reply sender is dropped, resolving reply with `Cancelled`
+        let _ = self.send(Message::Subscribe(key, Subscriber {
+            id: Some(id),
+            channel,
+            meta: None,
+        }, Some(sender)));
+        reply
+    }
+
+    #[inline]
+    ///Subscribes provided `channel` to receive messages of keys within `shard` out of `of` shards, potentially removing existing channel.
+    ///
+    ///Shard subscriber receives messages only for keys without subscriber. If there are shard
+    ///subscribers for different number of shards, those with the lowest `of` take precedence.
+    ///Use `shard_of` to find out shard of the key. Closed channel is removed on failed delivery.
+    ///
+    ///Panics if `shard` is not less than `of`.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe_shard(&self, shard: u32, of: u32, channel: S) -> Result<(), Cancelled> {
+        assert!(shard < of, "Shard must be less than number of shards");
+        self.send(Message::SubscribeShard(shard, of, channel))
+    }
+
+    #[inline(always)]
+    ///Removes channel subscribed to `shard` out of `of` shards.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn unsubscribe_shard(&self, shard: u32, of: u32) -> Result<(), Cancelled> {
+        self.send(Message::UnsubscribeShard(shard, of))
+    }
+
+    #[inline(always)]
+    ///Removes all channels subscribed on behalf of subscriber `id`
+    ///
+    ///This requires registry to go over all subscriptions, hence it should be used sparingly.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn unsubscribe_sender(&self, id: SubscriberId) -> Result<(), Cancelled> {
+        self.send(Message::UnsubscribeId(id))
+    }
+
+    ///Checks whether subscriber `id` still has any channel subscribed, that is not closed.
+    ///
+    ///This allows subscriber to detect that all its channels were removed (e.g. overwritten by
+    ///another subscriber or evicted as closed) and it needs to subscribe again.
+    ///As with `unsubscribe_sender`, registry goes over all subscriptions.
+    ///
+    ///Resolves to `Ok(true)` if `id` has alive channel, `Ok(false)` otherwise.
+    ///Resolves to `Err(Cancelled)` if registry is not running
+    pub fn is_subscribed(&self, id: SubscriberId) -> impl Future<Output = Result<bool, Cancelled>> {
+        let (sender, reply) = oneshot::channel();
+        //On failure reply sender is dropped, resolving reply with `Cancelled`
+        let _ = self.send(Message::IsSubscribed(id, sender));
+        reply
+    }
+
+    #[inline(always)]
+    ///Adds `channel` to the broadcast group of specified `key`.
+    ///
+    ///Unlike `subscribe`, existing channels are kept and every one of them receives messages sent
+    ///via `broadcast_to`.
+    ///
+    ///If `channel` is already closed according to `Sender::is_closed`, it is not added.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe_broadcast(&self, key: K, channel: S) -> Result<(), Cancelled> {
+        self.send(Message::SubscribeBroadcast(key, channel))
+    }
+
+    #[inline(always)]
+    ///Adds `channel` to the group of specified `key`, sharing messages with other channels of the group.
+    ///
+    ///Every message sent via `send_to` is delivered to exactly one channel of the group, in
+    ///round-robin order. Closed channels are removed from the group, passing message to the next
+    ///one. Once every channel of the group is closed, message is treated as undelivered (e.g.
+    ///passed to dead letter sink).
+    ///
+    ///Group is used only when `key` has no channel added via `subscribe`.
+    ///
+    ///If `channel` is already closed according to `Sender::is_closed`, it is not added.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe_group(&self, key: K, channel: S) -> Result<(), Cancelled> {
+        self.send(Message::SubscribeGroup(key, channel))
+    }
+
+    #[inline(always)]
+    ///Removes `channel` with specified `key` from registry, including its broadcast and round-robin groups
+    ///
+    ///Messages sent before this call are delivered to `channel` prior to its removal.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn unsubscribe(&self, key: K) -> Result<(), Cancelled> {
+        self.send(Message::Unsubscribe(key))
+    }
+
+    #[inline(always)]
+    ///Returns identifier of registry, to which channel sends messages.
+    ///
+    ///All clones of channel share the same identifier.
+    pub fn registry_id(&self) -> RegistryId {
+        self.state.id
+    }
+
+    #[inline(always)]
+    ///Returns total number of messages, accepted by subscribers so far.
+    ///
+    ///Every subscriber receiving broadcast message is counted.
+    ///The value is updated by registry after each delivery, so it lags behind.
+    pub fn delivered_count(&self) -> u64 {
+        self.state.delivered.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "metrics")]
+    #[inline]
+    ///Returns statistics of waker registrations, performed by registry whenever it goes idle.
+    ///
+    ///Large number of clones relative to reuses indicates executor, that provides new waker on
+    ///every poll, which makes registry clone it each time.
+    pub fn waker_stats(&self) -> WakerStats {
+        let (cloned, reused) = self.state.waker.registrations();
+        WakerStats {
+            cloned,
+            reused,
+        }
+    }
+
+    #[inline(always)]
+    ///Changes capacity of registry's queue, limited via `RegistryBuilder::drop_oldest`.
+    ///
+    ///Change is shared by all instances of `Channel` and takes effect for next message.
+    ///Messages already queued are kept, unless there are more than `capacity` of them, in which
+    ///case oldest ones are discarded right away, same as on overflow.
+    ///
+    ///Zero is treated as `1`.
+    ///
+    ///Returns `true` if capacity is changed
+    ///Returns `false` if queue is not limited
+    pub fn resize_inbound(&self, capacity: usize) -> bool {
+        self.channel.resize(core::cmp::max(capacity, 1), &self.state)
+    }
+
+    #[inline(always)]
+    ///Moves channel subscribed with `from` key to `to` key, potentially removing existing channel of `to`.
+    ///
+    ///Both keys are updated in one step, so there is no gap in delivery.
+    ///Nothing happens if `from` has no subscriber. Broadcast groups are not affected.
+    ///
+    ///Existing channel of `to` is reported as unsubscribed (or evicted if it is closed) and moved channel
+    ///is subject to the same limits as in `subscribe`, so it is removed if limits are exceeded.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn rekey(&self, from: K, to: K) -> Result<(), Cancelled> {
+        self.send(Message::Rekey(from, to, None))
+    }
+
+    ///Moves channel subscribed with `from` key to `to` key, replying whether channel is moved.
+    ///
+    ///Refer to `rekey` for details.
+    ///
+    ///Resolves to `Ok(true)` once registry moved channel, `Ok(false)` if `from` has no subscriber or channel is removed.
+    ///Resolves to `Err(Cancelled)` if registry is not running
+    pub fn rekey_ack(&self, from: K, to: K) -> impl Future<Output = Result<bool, Cancelled>> {
+        let (sender, reply) = oneshot::channel();
+        //On failure reply sender is dropped, resolving reply with `Cancelled`
+        let _ = self.send(Message::Rekey(from, to, Some(sender)));
+        reply
+    }
+
+    #[inline(always)]
+    ///Waits until registry starts processing messages, either via `run` or being polled as `Future`.
+    ///
+    ///Messages sent before registry starts are still queued and processed in order, hence it is
+    ///only necessary when startup of registry needs to be observed (e.g. to ensure it is spawned).
+    ///
+    ///Resolves to `Ok(())` once registry started
+    ///Resolves to `Err(Cancelled)` if registry is dropped without being started
+    pub fn wait_ready(&self) -> impl Future<Output = Result<(), Cancelled>> {
+        let state = self.state.clone();
+        core::future::poll_fn(move |ctx| state.ready.poll(ctx))
+    }
+
+    ///Drops channel, waiting until registry finishes processing.
+    ///
+    ///Registry finishes once it has handled all messages after every `Channel` is dropped or
+    ///once it is stopped, or when it is dropped. This allows to shutdown without racing against
+    ///deliveries still in progress, but other channels must be dropped too, otherwise registry keeps
+    ///running.
+    ///
+    ///Resolves once registry is finished.
+    pub fn join(self) -> impl Future<Output = ()> {
+        let state = self.state.clone();
+        drop(self);
+        core::future::poll_fn(move |ctx| state.finished.poll(ctx).map(|_| ()))
+    }
+
+    #[inline(always)]
+    ///Returns number of references to state, shared by registry and its channels.
+    ///
+    ///Reference is held by each `Channel` (including ones within `BoundSender` and other
+    ///wrappers) and `WeakChannel`, by `Registry` until it is dropped, by each `StopHandle` and by each pending future of
+    ///`wait_ready` or `join`. In debug builds, registry holds one more while it is running.
+    ///
+    ///Once registry is dropped and no other handle is alive, it is `1`, which allows tests to
+    ///assert that worker is torn down. Count is only a snapshot, if handles are used concurrently.
+    pub fn strong_refs(&self) -> usize {
+        Arc::strong_count(&self.state)
+    }
+
+    #[inline(always)]
+    ///Returns approximate number of keyed subscribers, without waiting for registry.
+    ///
+    ///Registry publishes number after handling each operation, so it lags behind operations,
+    ///which are still queued (e.g. it doesn't include `subscribe`, that is just sent), and it may
+    ///include closed subscribers, that are not yet removed. Broadcast groups, round-robin groups
+    ///and shards are not counted.
+    pub fn subscriber_count_hint(&self) -> usize {
+        self.state.subscribers.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    ///Returns whether registry panicked while processing messages.
+    ///
+    ///This allows to tell crash of registry from normal shutdown, as in both cases operations fail
+    ///with `Cancelled`. Flag is set once registry is dropped due to panic or panic is caught by
+    ///`Registry::run_supervised`, and is never reset.
+    pub fn worker_panicked(&self) -> bool {
+        self.state.panicked.load(Ordering::Acquire)
+    }
+
+    #[inline(always)]
+    ///Returns approximate number of messages, queued but not yet handled by registry.
+    pub fn pending(&self) -> usize {
+        self.state.pending.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    ///Returns total number of messages, discarded due to overflow of bounded queue so far.
+    ///
+    ///Refer to `RegistryBuilder::drop_oldest`.
+    pub fn overflowed_count(&self) -> u64 {
+        self.state.overflowed.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    ///Returns total number of discarded messages, which are lost without reaching dead letter sink so far.
+    ///
+    ///Discarded messages wait for registry to pass them to dead letter sink, but only latest
+    ///`capacity` of them are kept, so older ones are lost, if registry falls behind.
+    ///Refer to `RegistryBuilder::drop_oldest`.
+    pub fn overflow_lost_count(&self) -> u64 {
+        self.state.overflow_lost.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    ///Returns total number of messages, dropped due to expired TTL so far.
+    pub fn expired_count(&self) -> u64 {
+        self.state.expired.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    ///Releases memory retained by registry after subscribers removal.
+    ///
+    ///Registry keeps its capacity when subscribers are removed, so it is useful to call it after
+    ///large number of subscribers are gone.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn compact(&self) -> Result<(), Cancelled> {
+        self.send(Message::Compact)
+    }
+
+    #[inline]
+    ///Removes all subscribers, broadcast and round-robin groups, whose key doesn't satisfy `retain`.
+    ///
+    ///This is the same as calling `unsubscribe` for each such key, but without enumerating keys.
+    ///Predicate runs on registry's thread for each key, so it should be quick.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn retain<F: FnMut(&K) -> bool + Send + 'static>(&self, retain: F) -> Result<(), Cancelled> {
+        self.send(Message::Retain(Box::new(retain), None))
+    }
+
+    ///Removes all subscribers, broadcast and round-robin groups, whose key doesn't satisfy `retain`, replying with number of removed ones.
+    ///
+    ///Refer to `retain` for details. Subscriber and groups of the same key are counted separately.
+    ///
+    ///Resolves to `Ok(n)` once registry removed `n` subscribers and groups.
+    ///Resolves to `Err(Cancelled)` if registry is not running
+    pub fn retain_ack<F: FnMut(&K) -> bool + Send + 'static>(&self, retain: F) -> impl Future<Output = Result<usize, Cancelled>> {
+        let (sender, reply) = oneshot::channel();
+        //On failure reply sender is dropped, resolving reply with `Cancelled`
+        let _ = self.send(Message::Retain(Box::new(retain), Some(sender)));
+        reply
+    }
+
+    #[inline(always)]
+    ///Removes all subscribers, broadcast and round-robin groups.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn clear(&self) -> Result<(), Cancelled> {
+        self.retain(|_| false)
+    }
+
+    #[inline(always)]
+    ///Removes all subscribers, broadcast and round-robin groups, replying with number of removed ones.
+    ///
+    ///Refer to `retain_ack` for details.
+    pub fn clear_ack(&self) -> impl Future<Output = Result<usize, Cancelled>> {
+        self.retain_ack(|_| false)
+    }
+
+    ///Runs `with_keys` on registry's thread with keys of all subscribers, without cloning them.
+    ///
+    ///Keys are iterated in no particular order and do not include broadcast, round-robin groups and shards.
+    ///As closure blocks registry while running, it should be quick.
+    ///
+    ///Resolves to `Ok(())` once registry ran closure
+    ///Resolves to `Err(Cancelled)` if registry is not running, in which case closure is dropped
+    pub fn with_keys<F: FnOnce(&mut dyn Iterator<Item = &K>) + Send + 'static>(&self, with_keys: F) -> impl Future<Output = Result<(), Cancelled>> {
+        let (sender, reply) = oneshot::channel();
+        //On failure reply sender is dropped, resolving reply with `Cancelled`
+        let _ = self.send(Message::WithKeys(Box::new(with_keys), sender));
+        reply
+    }
+
+    ///Runs `with_subscribers` on registry's thread with every subscriber and its metadata.
+    ///
+    ///Metadata is `None` for subscribers, that are subscribed without it (e.g. via `subscribe`).
+    ///This allows to query registry as directory of subscribers (e.g. to find ones with particular capability).
+    ///Subscribers are iterated in no particular order and do not include broadcast, round-robin groups and shards.
+    ///As closure blocks registry while running, it should be quick.
+    ///
+    ///Resolves to `Ok(())` once registry ran closure
+    ///Resolves to `Err(Cancelled)` if registry is not running, in which case closure is dropped
+    pub fn with_subscribers<F: FnOnce(&mut dyn Iterator<Item = (&K, &S, Option<&M>)>) + Send + 'static>(&self, with_subscribers: F) -> impl Future<Output = Result<(), Cancelled>> {
+        let (sender, reply) = oneshot::channel();
+        //On failure reply sender is dropped, resolving reply with `Cancelled`
+        let _ = self.send(Message::WithSubscribers(Box::new(with_subscribers), sender));
+        reply
+    }
+
+    #[inline(always)]
+    ///Removes all subscribers, which report being closed via `Sender::is_closed`.
+    ///
+    ///Registry removes closed subscribers only on failed delivery, so subscribers of keys that
+    ///rarely receive messages may linger. Removed subscribers are reported via
+    ///`RegistryBuilder::on_evict`.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn prune_closed(&self) -> Result<(), Cancelled> {
+        self.send(Message::PruneClosed)
+    }
+
+    #[inline(always)]
+    ///Sends message `msg` over to channel registered by `key`.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to(&self, key: K, msg: T) -> Result<(), Cancelled> {
+        self.send(Message::Msg(key, msg))
+    }
+
+    #[inline]
+    ///Sends message `msg` over to channel registered by `key`, unless registry handles it after `ttl`.
+    ///
+    ///Expired message is passed to dead letter sink, if any, and counted by `expired_count`.
+    ///This allows to shed stale messages, when registry falls behind.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to_ttl(&self, key: K, msg: T, ttl: time::Duration) -> Result<(), Cancelled> {
+        match std::time::Instant::now().checked_add(ttl) {
+            Some(deadline) => self.send(Message::MsgTtl(key, msg, deadline)),
+            //Deadline cannot be reached anyway
+            None => self.send(Message::Msg(key, msg)),
+        }
+    }
+
+    #[inline(always)]
+    ///Sends message `msg` over to channel registered by `key`, returning number of pending messages.
+    ///
+    ///Returned number is approximate queue depth right after enqueuing `msg`, allowing producer to
+    ///throttle itself when registry falls behind.
+    ///
+    ///Returns `Ok(usize)` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to_backpressured(&self, key: K, msg: T) -> Result<usize, Cancelled> {
+        self.send_raw(Message::Msg(key, msg)).map_err(|_| Cancelled::NOT_RUNNING)
+    }
+
+    #[inline(always)]
+    ///Sends message `msg` over to channel registered by `key` with priority `prio`.
+    ///
+    ///If registry is configured via `RegistryBuilder::prioritize`, messages with higher priority
+    ///are delivered ahead of queued messages with lower priority. Otherwise, priority is ignored
+    ///and this is the same as `send_to`.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to_prio(&self, key: K, msg: T, prio: u8) -> Result<(), Cancelled> {
+        self.send(Message::MsgPrio(key, msg, prio))
+    }
+
+    #[inline(always)]
+    ///Sends message, created by `make`, over to channel registered by `key`.
+    ///
+    ///`make` is invoked on registry's thread only if `key` has subscriber, avoiding cost of creating
+    ///message nobody listens to. This requires to allocate closure, so it is only worth it when
+    ///message is expensive to create.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to_with<F: FnOnce() -> T + Send + 'static>(&self, key: K, make: F) -> Result<(), Cancelled> {
+        self.send(Message::MsgWith(key, Box::new(make)))
+    }
+
+    #[inline(always)]
+    ///Sends message `msg` over to channel registered by `primary` key, falling back to `fallback` key
+    ///if `primary` has no subscriber or its subscriber is closed.
+    ///
+    ///If neither can accept message, it is passed to dead letter sink, if configured, or dropped.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to_or(&self, primary: K, fallback: K, msg: T) -> Result<(), Cancelled> {
+        self.send(Message::MsgOr(primary, fallback, msg))
+    }
+
+    ///Sends message `msg` over to channel registered by `key`, replying with outcome of delivery.
+    ///
+    ///Unlike `send_to`, which only reports whether registry accepted message, this allows producer
+    ///to observe whether subscriber actually accepted it. Undelivered message is still passed to
+    ///dead letter sink, if any.
+    ///
+    ///Resolves to `Ok(())` once subscriber accepted message.
+    ///Resolves to `Err(SubscriberGone)` if there is no subscriber, subscriber is closed or registry is not running
+    pub fn send_to_result(&self, key: K, msg: T) -> impl Future<Output = Result<(), SubscriberGone>> {
+        let (sender, reply) = oneshot::channel();
+        //On failure reply sender is dropped, resolving reply with `Cancelled`
+        let _ = self.send(Message::MsgResult(key, msg, sender));
+        async move {
+            match reply.await {
+                Ok(result) => result,
+                Err(_) => Err(SubscriberGone::NotRunning),
+            }
+        }
+    }
+
+    ///Sends message `msg` over to channel registered by `key`, replying with message back if there is no subscriber.
+    ///
+    ///Unlike `send_to`, which drops message to key without subscriber, this allows producer to
+    ///reuse message (e.g. for fallback path) without constructing it again. Subscriber is looked up
+    ///by registry, so there is no race with concurrent subscription as with checking key beforehand.
+    ///Message is lost if registry is not running.
+    ///
+    ///Resolves to `Ok(SendOutcome)` once registry handled message
+    ///Resolves to `Err(Cancelled)` if registry is not running
+    pub fn send_to_if_present(&self, key: K, msg: T) -> impl Future<Output = Result<SendOutcome<T>, Cancelled>> {
+        let (sender, reply) = oneshot::channel();
+        //On failure reply sender is dropped, resolving reply with `Cancelled`
+        let _ = self.send(Message::MsgIfPresent(key, msg, sender));
+        reply
+    }
+
+    ///Sends message `msg` over to channel registered by `key`, blocking until registry confirms delivery.
+    ///
+    ///This is intended for threads without async executor.
+    ///It must not be called from registry's thread (e.g. within `Sender::try_send`) as it would
+    ///block registry itself, which is detected with panic in debug mode.
+    ///
+    ///Returns `Ok(true)` if subscriber accepted message.
+    ///Returns `Ok(false)` if there is no subscriber, subscriber is closed or confirmation is not
+    ///received within `timeout`.
+    ///Returns `Err(Cancelled)` if registry is not running or stopped before confirming
+    pub fn send_to_confirm_blocking(&self, key: K, msg: T, timeout: time::Duration) -> Result<bool, Cancelled> {
+        let (sender, reply) = oneshot::channel();
+        self.state.assert_not_worker();
+        self.send(Message::MsgConfirm(key, msg, sender))?;
+        reply.wait_timeout(timeout).map(|delivered| delivered.unwrap_or(false))
+    }
+
+    ///Subscribes provided `channel` with specified `key`, blocking until registry confirms subscription.
+    ///
+    ///All clones of channel share the same queue, so operations sent after `subscribe` returns are
+    ///already handled after subscription. Confirmation additionally guarantees subscription took
+    ///effect before returning, which is useful when other parties are notified by other means.
+    ///
+    ///It must not be called from registry's thread, same as `send_to_confirm_blocking`.
+    ///
+    ///Returns `Ok(true)` if `channel` is subscribed.
+    ///Returns `Ok(false)` if subscription is rejected (refer to `try_subscribe`) or confirmation
+    ///is not received within `timeout`.
+    ///Returns `Err(Cancelled)` if registry is not running or stopped before confirming
+    pub fn subscribe_blocking(&self, key: K, channel: S, timeout: time::Duration) -> Result<bool, Cancelled> {
+        let (sender, reply) = oneshot::channel();
+        self.state.assert_not_worker();
+        self.send(Message::Subscribe(key, Subscriber {
+            id: None,
+            channel,
+            meta: None,
+        }, Some(sender)))?;
+        reply.wait_timeout(timeout).map(|subscribed| subscribed.unwrap_or(false))
+    }
+
+    ///Forwards every message of `recv` via `send_to`, blocking until `recv` is disconnected.
+    ///
+    ///This is intended to connect producer of keyed messages to registry, running on dedicated
+    ///thread. It must not be called from registry's thread, same as `send_to_confirm_blocking`.
+    ///
+    ///Returns `Ok(number)` of forwarded messages once all senders of `recv` are gone.
+    ///Returns `Err((key, msg))` with message, that could not be sent due to registry not running,
+    ///leaving remaining messages within `recv`.
+    pub fn pump_from(&self, recv: &mpsc::Receiver<(K, T)>) -> Result<usize, (K, T)> {
+        self.state.assert_not_worker();
+        let mut forwarded = 0;
+        for (key, msg) in recv.iter() {
+            match self.send_raw(Message::Msg(key, msg)) {
+                Ok(_) => forwarded += 1,
+                Err(Message::Msg(key, msg)) => return Err((key, msg)),
+                Err(_) => unreachable!(),
+            }
+        }
+        Ok(forwarded)
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T> + Clone, M> Channel<K, T, S, M> {
+    #[inline]
+    ///Subscribes clones of provided `channel` with every key of `keys`, potentially removing existing channels.
+    ///
+    ///This is the same as calling `subscribe` for each key, but handled by registry within single message.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe_keys<I: IntoIterator<Item = K>>(&self, keys: I, channel: S) -> Result<(), Cancelled> {
+        self.send(Message::SubscribeMany(keys.into_iter().collect(), channel, S::clone))
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Clone + Send, S: Sender<T>, M> Channel<K, T, S, M> {
+    #[inline(always)]
+    ///Sets `channel` as catch-all subscriber, receiving clone of every message delivered to a keyed subscriber.
+    ///
+    ///Clone is passed to `channel` after keyed subscriber accepted message.
+    ///Undelivered messages and broadcasts are not passed to it.
+    ///Previous catch-all subscriber is replaced, while closed one is removed on next delivery attempt.
+    ///
+    ///If `channel` is already closed according to `Sender::is_closed`, it is ignored.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe_all(&self, channel: S) -> Result<(), Cancelled> {
+        self.send(Message::SubscribeTap(channel, T::clone))
+    }
+
+    #[inline(always)]
+    ///Sends clone of message `msg` to every channel within broadcast group of `key`.
+    ///
+    ///Closed channels are removed from the group.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn broadcast_to(&self, key: K, msg: T) -> Result<(), Cancelled> {
+        self.send(Message::Broadcast(key, msg, T::clone, None))
+    }
+
+    #[inline(always)]
+    ///Sends clone of message `msg` to every subscriber, regardless of its key, including broadcast groups.
+    ///
+    ///Closed channels are removed.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn broadcast_all(&self, msg: T) -> Result<(), Cancelled> {
+        self.send(Message::BroadcastAll(msg, T::clone))
+    }
+
+    #[inline]
+    ///Sends clone of message `msg` to every key returned by `select`, which is given keys of all subscribers.
+    ///
+    ///Selector runs on registry's thread, so it sees current subscribers without round trip, but
+    ///it blocks registry while running and has to go over all keys, hence it should be quick.
+    ///Keys are given in no particular order and do not include broadcast, round-robin groups and shards.
+    ///
+    ///Message is delivered to each selected key as via `send_to`, including keys without
+    ///subscriber, and closed subscribers are removed.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to_selected<F: FnOnce(&[&K]) -> Vec<K> + Send + 'static>(&self, select: F, msg: T) -> Result<(), Cancelled> {
+        self.send(Message::MsgSelected(Box::new(select), msg, T::clone))
+    }
+
+    #[inline]
+    ///Sends message `msg` to every key of `keys`, as via `send_to`, within single registry operation.
+    ///
+    ///Message is cloned for all keys except the last one, which receives original message, so
+    ///sending to `N` keys takes one clone less than `N` calls of `send_to` and wakes registry once.
+    ///
+    ///Delivery to each key is independent: keys without subscriber, or with closed subscriber,
+    ///do not prevent delivery to the rest of keys, and their copies are passed to dead letter sink.
+    ///Duplicate keys receive message multiple times.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to_keys<I: IntoIterator<Item = K>>(&self, keys: I, msg: T) -> Result<(), Cancelled> {
+        self.send(Message::MsgMulti(keys.into_iter().collect(), msg, T::clone))
+    }
+
+    ///Sends clone of message `msg` to every channel within broadcast group of `key`, replying with number of channels that accepted it.
+    ///
+    ///Closed channels are removed from the group and not counted.
+    ///
+    ///Resolves to `Ok(n)` once registry processed message, `n` being `0` if nobody received message.
+    ///Resolves to `Err(Cancelled)` if registry is not running
+    pub fn broadcast_to_ack(&self, key: K, msg: T) -> impl Future<Output = Result<usize, Cancelled>> {
+        let (sender, reply) = oneshot::channel();
+        //On failure reply sender is dropped, resolving reply with `Cancelled`
+        let _ = self.send(Message::Broadcast(key, msg, T::clone, Some(sender)));
+        reply
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send + Sync, S: Sender<Arc<T>>> Channel<K, Arc<T>, S> {
+    #[inline(always)]
+    ///Wraps message `msg` into `Arc` once and broadcasts it to every channel within broadcast group of `key`.
+    ///
+    ///Subscribers share the same message, therefore `T` is not required to be `Clone`.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn broadcast_shared_to(&self, key: K, msg: T) -> Result<(), Cancelled> {
+        self.broadcast_to(key, Arc::new(msg))
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> fmt::Debug for Channel<K, T, S, M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Channel")
+           .field("connected", &!(self.state.closed.load(Ordering::Acquire) || self.state.stopped.load(Ordering::Acquire)))
+           .field("senders", &self.state.senders.load(Ordering::Relaxed))
+           .field("pending", &self.state.pending.load(Ordering::Relaxed))
+           .finish()
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Clone for Channel<K, T, S, M> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        self.state.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            state: self.state.clone(),
+            channel: self.channel.clone(),
+            control: self.control.clone(),
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Drop for Channel<K, T, S, M> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        //Counter is used instead of `Arc::strong_count`, as concurrently dropped senders could
+        //observe each other's reference and both miss being last.
+        //Registry is disconnected once counter reaches zero, even if `WeakChannel` keeps queue open
+        let last = self.state.senders.fetch_sub(1, Ordering::AcqRel) == 1;
+
+        //Registry, blocked on queue, which is kept open by `WeakChannel`, has to be woken by message
+        #[cfg(feature = "blocking")]
+        if last && self.state.weak.load(Ordering::Acquire) > 0 {
+            let _ = self.enqueue(Message::Wake, false);
+        }
+
+        unsafe {
+            ManuallyDrop::drop(&mut self.channel);
+            ManuallyDrop::drop(&mut self.control);
+        }
+
+        if last {
+            //If it is last sender
+            //In order to terminate task
+            //Wake it up, if it is still listening
+            self.state.waker.wake();
+        }
+    }
+}
+
+#[inline(always)]
+fn shard_hash<K: Hash>(key: &K) -> u64 {
+    use core::hash::Hasher;
+
+    //Hasher with fixed keys, so that shard of the key is the same for every registry
+    #[allow(deprecated)]
+    let mut hasher = std::hash::SipHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[inline]
+///Returns shard of `key` out of `of` shards, as used by `Channel::subscribe_shard`.
+///
+///Shard is based on hash of `key`, that is the same across registries, but may change between
+///versions of Rust.
+///
+///Panics if `of` is zero.
+pub fn shard_of<K: Hash>(key: &K, of: u32) -> u32 {
+    (shard_hash(key) % u64::from(of)) as u32
+}
+
+#[inline(always)]
+///Creates waker, which unparks `thread` on wake.
+///
+///This is the same waker that is used by `Registry::run`, allowing to drive `Registry` future
+///using custom loop on parked thread.
+pub fn thread_waker(thread: std::thread::Thread) -> task::Waker {
+    waker::thread::waker(thread)
+}
+
+#[inline(always)]
+///Creates waker, which invokes `notify` on every wake up.
+///
+///This allows to integrate registry with event loop (e.g. by writing to `eventfd` watched by
+///`epoll`), driving registry via `Registry::poll_with` once notified. `notify` is invoked by
+///senders, so it should be quick and must not drive registry itself.
+pub fn notify_waker<F: Fn() + Send + Sync + 'static>(notify: F) -> task::Waker {
+    waker::notify::waker(notify)
+}
+
+#[inline(always)]
+///Creates new registry returning sending channel and registry task
+///
+///Use `RegistryBuilder` to configure registry.
+pub fn registry<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>>() -> (Channel<K, T, S>, Registry<K, T, S>) {
+    RegistryBuilder::new().build()
+}
+
+#[inline(always)]
+///Creates new registry, storing metadata `M` of subscribers, returning sending channel and registry task
+///
+///Refer to `Channel::subscribe_with_meta` for details.
+pub fn registry_with_meta<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M>() -> (Channel<K, T, S, M>, Registry<K, T, S, M>) {
+    RegistryBuilder::with_meta().build()
+}
+
+#[inline]
+///Creates new registry, running it on new thread, and returns sending channel with thread's handle.
+///
+///Thread finishes once registry is cancelled, returning reason from `Registry::run`.
+///Panics if thread cannot be spawned, as `std::thread::spawn`. Use `registry_spawned_with` to configure thread.
+pub fn registry_spawned<K: PartialEq + Eq + Hash + Send + 'static, T: Send + 'static, S: Sender<T> + Send + 'static>() -> (Channel<K, T, S>, std::thread::JoinHandle<Cancelled>) {
+    match registry_spawned_with(std::thread::Builder::new().name("pochta".to_owned())) {
+        Ok(result) => result,
+        Err(error) => panic!("Failed to spawn registry thread: {}", error),
+    }
+}
+
+///Creates new registry, running it on new thread configured by `builder`, and returns sending channel with thread's handle.
+///
+///This allows to set name of thread and its stack size, which might need to be increased if subscribers
+///do a lot of work within `Sender::try_send`.
+///
+///Returns `Err` if thread cannot be spawned.
+pub fn registry_spawned_with<K: PartialEq + Eq + Hash + Send + 'static, T: Send + 'static, S: Sender<T> + Send + 'static>(builder: std::thread::Builder) -> std::io::Result<(Channel<K, T, S>, std::thread::JoinHandle<Cancelled>)> {
+    let (channel, mut registry) = registry();
+    let worker = builder.spawn(move || registry.run())?;
+    Ok((channel, worker))
+}
+
+#[inline(always)]
+///Creates new registry, which delivers only latest message per key out of queued messages.
+///
+///Refer to `RegistryBuilder::coalesce` for details.
+pub fn coalescing_registry<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>>() -> (Channel<K, T, S>, Registry<K, T, S>) {
+    RegistryBuilder::new().coalesce().build()
+}
+
+#[inline(always)]
+///Creates new registry, which delivers queued messages newest first.
+///
+///Refer to `RegistryBuilder::lifo` for details.
+pub fn lifo_registry<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>>() -> (Channel<K, T, S>, Registry<K, T, S>) {
+    RegistryBuilder::new().lifo().build()
+}
+
+#[inline(always)]
+///Creates new registry, which holds at most `capacity` queued messages, discarding oldest message on overflow.
+///
+///Refer to `RegistryBuilder::drop_oldest` for details.
+pub fn registry_bounded_drop_oldest<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>>(capacity: usize) -> (Channel<K, T, S>, Registry<K, T, S>) {
+    RegistryBuilder::new().drop_oldest(capacity).build()
+}
+
+#[inline(always)]
+///Creates new registry with pre-installed `subscribers`
+///
+///This allows to replace registry, using subscribers of the `Registry::finish`.
+///Subscribers that are already closed are removed on first delivery attempt.
+pub fn registry_from_map<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>>(subscribers: HashMap<K, S>) -> (Channel<K, T, S>, Registry<K, T, S>) {
+    RegistryBuilder::new().subscribers(subscribers).build()
+}
+
+#[inline(always)]
+///Creates new registry, which delivers messages as `Arc<T>`
+///
+///This allows to broadcast messages without requiring `T: Clone`, as only pointer is cloned per subscriber.
+pub fn shared_registry<K: PartialEq + Eq + Hash, T: Send + Sync, S: Sender<Arc<T>>>() -> (Channel<K, Arc<T>, S>, Registry<K, Arc<T>, S>) {
+    registry()
 }
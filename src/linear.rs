@@ -0,0 +1,164 @@
+use core::{fmt, task};
+use core::future::Future;
+use core::pin::Pin;
+
+use crate::driver::{self, Driver};
+use crate::{deliver, is_alive, Cancelled, Sender};
+
+pub(crate) enum LinearMessage<K, T: Send, S: Sender<T>> {
+    Subscribe(K, S),
+    Unsubscribe(K),
+    Msg(K, T),
+}
+
+///Channel of `LinearRegistry`.
+pub struct LinearChannel<K: Eq, T: Send, S: Sender<T>> {
+    channel: driver::Tx<LinearMessage<K, T, S>>,
+}
+
+impl<K: Eq, T: Send, S: Sender<T>> LinearChannel<K, T, S> {
+    #[inline(always)]
+    ///Subscribes provided `channel` with specified `key`, potentially removing existing channel
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe(&self, key: K, channel: S) -> Result<(), Cancelled> {
+        self.channel.send(LinearMessage::Subscribe(key, channel))
+    }
+
+    #[inline(always)]
+    ///Removes `channel` with specified `key` from registry
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn unsubscribe(&self, key: K) -> Result<(), Cancelled> {
+        self.channel.send(LinearMessage::Unsubscribe(key))
+    }
+
+    #[inline(always)]
+    ///Sends message `msg` to channel with specified `key`
+    ///
+    ///Message to key without subscriber is dropped.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to(&self, key: K, msg: T) -> Result<(), Cancelled> {
+        self.channel.send(LinearMessage::Msg(key, msg))
+    }
+}
+
+impl<K: Eq, T: Send, S: Sender<T>> Clone for LinearChannel<K, T, S> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<K: Eq, T: Send, S: Sender<T>> fmt::Debug for LinearChannel<K, T, S> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("LinearChannel")
+           .field("id", &self.channel.state.id)
+           .finish()
+    }
+}
+
+///Registry of subscribers, looked up by linear search over keys.
+///
+///This is lightweight alternative to `Registry` for keys, that do not implement `Hash` or are
+///expensive to hash while cheap to compare. Subscribers are stored in `Vec`, so each operation
+///compares key against every subscribed key, taking `O(n)` time, unlike `O(1)` of `Registry`.
+///For cheap keys (e.g. integers), it is faster than `Registry` up to a couple hundreds of
+///subscribers and falls behind beyond that, while for costly hashing crossover point is higher.
+///It only supports subscription and direct delivery of messages.
+///
+///As `Registry`, it can be either ran on dedicated thread via `run` or polled as `Future`.
+pub struct LinearRegistry<K: Eq, T: Send, S: Sender<T>> {
+    registry: Vec<(K, S)>,
+    recv: driver::Rx<LinearMessage<K, T, S>>,
+}
+
+impl<K: Eq, T: Send, S: Sender<T>> LinearRegistry<K, T, S> {
+    ///Process registry messages until cancelled.
+    ///
+    ///This function blocks, until all sending channels gets closed
+    pub fn run(&mut self) -> Cancelled {
+        self.run_queue()
+    }
+
+    ///Processes all queued messages, returning subscribers in order of subscription.
+    pub fn finish(mut self) -> Vec<(K, S)> {
+        while let Ok(message) = self.recv.try_recv() {
+            self.handle(message);
+        }
+        core::mem::take(&mut self.registry)
+    }
+
+    #[inline(always)]
+    fn position(&self, key: &K) -> Option<usize> {
+        self.registry.iter().position(|(subscribed, _)| subscribed == key)
+    }
+}
+
+impl<K: Eq, T: Send, S: Sender<T>> Driver for LinearRegistry<K, T, S> {
+    type Message = LinearMessage<K, T, S>;
+
+    #[inline(always)]
+    fn rx(&self) -> &driver::Rx<Self::Message> {
+        &self.recv
+    }
+
+    fn handle(&mut self, message: LinearMessage<K, T, S>) {
+        match message {
+            LinearMessage::Subscribe(key, channel) => match self.position(&key) {
+                Some(idx) => self.registry[idx].1 = channel,
+                None => self.registry.push((key, channel)),
+            },
+            LinearMessage::Unsubscribe(key) => if let Some(idx) = self.position(&key) {
+                self.registry.swap_remove(idx);
+            },
+            LinearMessage::Msg(key, message) => if let Some(idx) = self.position(&key) {
+                let result = deliver(&self.registry[idx].1, message);
+                if result.is_ok() {
+                    self.recv.state().on_delivered(1);
+                } else if !is_alive(result) {
+                    self.registry.swap_remove(idx);
+                }
+            },
+        }
+    }
+}
+
+impl<K: Eq, T: Send, S: Sender<T>> fmt::Debug for LinearRegistry<K, T, S> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("LinearRegistry")
+           .field("subscribers", &self.registry.len())
+           .finish()
+    }
+}
+
+impl<K: Eq, T: Send, S: Sender<T>> Unpin for LinearRegistry<K, T, S> {}
+
+impl<K: Eq, T: Send, S: Sender<T>> Future for LinearRegistry<K, T, S> {
+    type Output = Cancelled;
+
+    #[inline(always)]
+    fn poll(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        self.get_mut().poll_queue(ctx.waker())
+    }
+}
+
+///Creates new registry for keys without `Hash`, returning sending channel and registry task
+///
+///Refer to `LinearRegistry` for details.
+pub fn linear_registry<K: Eq, T: Send, S: Sender<T>>() -> (LinearChannel<K, T, S>, LinearRegistry<K, T, S>) {
+    let (channel, recv) = driver::channel();
+
+    (LinearChannel {
+        channel,
+    }, LinearRegistry {
+        registry: Vec::new(),
+        recv,
+    })
+}
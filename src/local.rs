@@ -0,0 +1,224 @@
+use core::{fmt, task};
+use core::cell::RefCell;
+use core::future::Future;
+use core::hash::Hash;
+use core::pin::Pin;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+use crate::{deliver, is_alive, Cancelled, Sender};
+
+enum LocalMessage<K, T, S> {
+    Subscribe(K, S),
+    Unsubscribe(K),
+    Msg(K, T),
+}
+
+struct Shared<K, T, S> {
+    queue: VecDeque<LocalMessage<K, T, S>>,
+    waker: Option<task::Waker>,
+    //Number of alive `LocalChannel` instances
+    senders: usize,
+    //Whether `LocalRegistry` is dropped
+    closed: bool,
+}
+
+//Wakes registry outside of borrow, as waker may poll it right away
+#[inline(always)]
+fn wake(waker: Option<task::Waker>) {
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+///Channel of `LocalRegistry`, which can be used only within thread of registry.
+pub struct LocalChannel<K, T, S> {
+    shared: Rc<RefCell<Shared<K, T, S>>>,
+}
+
+impl<K: PartialEq + Eq + Hash, T, S: Sender<T>> LocalChannel<K, T, S> {
+    fn send(&self, msg: LocalMessage<K, T, S>) -> Result<(), Cancelled> {
+        let waker = {
+            let mut shared = self.shared.borrow_mut();
+            if shared.closed {
+                return Err(Cancelled::NOT_RUNNING);
+            }
+            shared.queue.push_back(msg);
+            shared.waker.take()
+        };
+        wake(waker);
+        Ok(())
+    }
+
+    #[inline(always)]
+    ///Subscribes provided `channel` with specified `key`, potentially removing existing channel
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe(&self, key: K, channel: S) -> Result<(), Cancelled> {
+        self.send(LocalMessage::Subscribe(key, channel))
+    }
+
+    #[inline(always)]
+    ///Removes `channel` with specified `key` from registry
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn unsubscribe(&self, key: K) -> Result<(), Cancelled> {
+        self.send(LocalMessage::Unsubscribe(key))
+    }
+
+    #[inline(always)]
+    ///Sends message `msg` to channel with specified `key`
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send_to(&self, key: K, msg: T) -> Result<(), Cancelled> {
+        self.send(LocalMessage::Msg(key, msg))
+    }
+}
+
+impl<K, T, S> Clone for LocalChannel<K, T, S> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        self.shared.borrow_mut().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<K, T, S> Drop for LocalChannel<K, T, S> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        let waker = {
+            let mut shared = self.shared.borrow_mut();
+            shared.senders -= 1;
+            match shared.senders {
+                //Wake registry to finish, as it has nothing else to process
+                0 => shared.waker.take(),
+                _ => None,
+            }
+        };
+        wake(waker);
+    }
+}
+
+impl<K, T, S> fmt::Debug for LocalChannel<K, T, S> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shared = self.shared.borrow();
+        fmt.debug_struct("LocalChannel")
+           .field("pending", &shared.queue.len())
+           .field("closed", &shared.closed)
+           .finish()
+    }
+}
+
+///Registry for single-threaded executors, that is polled as `Future`.
+///
+///Unlike `Registry`, neither registry nor its channels are `Send`, hence there is no requirement
+///for keys, messages and subscribers to be `Send` either.
+///It only supports subscription and direct delivery of messages.
+///
+///Subscriber is free to send messages via `LocalChannel` when receiving message, as registry
+///never holds its queue while delivering.
+pub struct LocalRegistry<K, T, S> {
+    shared: Rc<RefCell<Shared<K, T, S>>>,
+    registry: HashMap<K, S>,
+}
+
+impl<K: PartialEq + Eq + Hash, T, S: Sender<T>> LocalRegistry<K, T, S> {
+    #[inline(always)]
+    fn next(&self) -> Option<LocalMessage<K, T, S>> {
+        self.shared.borrow_mut().queue.pop_front()
+    }
+
+    fn handle(&mut self, message: LocalMessage<K, T, S>) {
+        match message {
+            LocalMessage::Subscribe(key, channel) => {
+                self.registry.insert(key, channel);
+            },
+            LocalMessage::Unsubscribe(key) => {
+                self.registry.remove(&key);
+            },
+            LocalMessage::Msg(key, message) => if let Some(channel) = self.registry.get(&key) {
+                if !is_alive(deliver(channel, message)) {
+                    self.registry.remove(&key);
+                }
+            },
+        }
+    }
+
+    ///Processes all queued messages, returning subscribers.
+    pub fn finish(mut self) -> HashMap<K, S> {
+        while let Some(message) = self.next() {
+            self.handle(message);
+        }
+        core::mem::take(&mut self.registry)
+    }
+}
+
+impl<K, T, S> Drop for LocalRegistry<K, T, S> {
+    fn drop(&mut self) {
+        let (queue, waker) = {
+            let mut shared = self.shared.borrow_mut();
+            shared.closed = true;
+            (core::mem::take(&mut shared.queue), shared.waker.take())
+        };
+        //Messages are dropped outside of borrow, as they may hold channels
+        drop(queue);
+        drop(waker);
+    }
+}
+
+impl<K, T, S> fmt::Debug for LocalRegistry<K, T, S> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("LocalRegistry")
+           .field("subscribers", &self.registry.len())
+           .field("pending", &self.shared.borrow().queue.len())
+           .finish()
+    }
+}
+
+impl<K, T, S> Unpin for LocalRegistry<K, T, S> {}
+
+impl<K: PartialEq + Eq + Hash, T, S: Sender<T>> Future for LocalRegistry<K, T, S> {
+    type Output = Cancelled;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = self.get_mut();
+        loop {
+            match this.next() {
+                Some(message) => this.handle(message),
+                None => {
+                    let mut shared = this.shared.borrow_mut();
+                    if shared.senders == 0 {
+                        break task::Poll::Ready(Cancelled::DISCONNECTED);
+                    }
+                    //Queue is checked under the same borrow, so no message can be missed
+                    shared.waker = Some(ctx.waker().clone());
+                    break task::Poll::Pending;
+                },
+            }
+        }
+    }
+}
+
+///Creates new single-threaded registry returning sending channel and registry task
+///
+///Refer to `LocalRegistry` for details.
+pub fn local_registry<K: PartialEq + Eq + Hash, T, S: Sender<T>>() -> (LocalChannel<K, T, S>, LocalRegistry<K, T, S>) {
+    let shared = Rc::new(RefCell::new(Shared {
+        queue: VecDeque::new(),
+        waker: None,
+        senders: 1,
+        closed: false,
+    }));
+
+    (LocalChannel {
+        shared: shared.clone(),
+    }, LocalRegistry {
+        shared,
+        registry: HashMap::new(),
+    })
+}
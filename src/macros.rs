@@ -0,0 +1,116 @@
+#[macro_export]
+///Defines channel with concrete key, message and sender types.
+///
+///Generated type wraps `Channel`, providing constructor `new`, that creates it together with
+///`Registry`, and typed `subscribe`, `unsubscribe` and `send_to`. Remaining methods of `Channel`
+///are available via `Deref` or `into_inner`. Optional `builder` closure configures
+///`RegistryBuilder`, used by `new`.
+///
+///## Usage
+///
+///```rust
+///use std::sync::mpsc;
+///
+///pochta::define_registry! {
+///    ///Channel of chat rooms
+///    pub struct Rooms {
+///        key: u64,
+///        message: String,
+///        sender: mpsc::Sender<String>,
+///        builder: |builder| builder.poll_budget(64),
+///    }
+///}
+///
+///let (rooms, registry) = Rooms::new();
+///let (send, recv) = mpsc::channel();
+///rooms.subscribe(1, send).expect("Registry to run");
+///rooms.send_to(1, "hello".to_owned()).expect("Registry to run");
+///drop(rooms);
+///registry.finish();
+///assert_eq!(recv.recv().expect("Delivered"), "hello");
+///```
+macro_rules! define_registry {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            key: $key:ty,
+            message: $msg:ty,
+            sender: $sender:ty $(,)?
+        }
+    ) => {
+        $crate::define_registry! {
+            $(#[$meta])*
+            $vis struct $name {
+                key: $key,
+                message: $msg,
+                sender: $sender,
+                builder: |builder| builder,
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            key: $key:ty,
+            message: $msg:ty,
+            sender: $sender:ty,
+            builder: $builder:expr $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug)]
+        $vis struct $name($crate::Channel<$key, $msg, $sender>);
+
+        impl $name {
+            #[inline]
+            ///Creates new registry returning sending channel and registry task
+            $vis fn new() -> (Self, $crate::Registry<$key, $msg, $sender>) {
+                let builder: fn($crate::RegistryBuilder<$key, $msg, $sender>) -> $crate::RegistryBuilder<$key, $msg, $sender> = $builder;
+                let (channel, registry) = builder($crate::RegistryBuilder::new()).build();
+                (Self(channel), registry)
+            }
+
+            #[inline(always)]
+            ///Subscribes provided `channel` with specified `key`, potentially removing existing channel.
+            ///
+            ///Returns `Ok(())` if registry is still running
+            ///Returns `Err(Cancelled)` if message ignored due to registry not running
+            $vis fn subscribe(&self, key: $key, channel: $sender) -> Result<(), $crate::Cancelled> {
+                self.0.subscribe(key, channel)
+            }
+
+            #[inline(always)]
+            ///Removes channel subscribed with `key`.
+            ///
+            ///Returns `Ok(())` if registry is still running
+            ///Returns `Err(Cancelled)` if message ignored due to registry not running
+            $vis fn unsubscribe(&self, key: $key) -> Result<(), $crate::Cancelled> {
+                self.0.unsubscribe(key)
+            }
+
+            #[inline(always)]
+            ///Sends message `msg` over to channel registered by `key`.
+            ///
+            ///Returns `Ok(())` if registry is still running
+            ///Returns `Err(Cancelled)` if message ignored due to registry not running
+            $vis fn send_to(&self, key: $key, msg: $msg) -> Result<(), $crate::Cancelled> {
+                self.0.send_to(key, msg)
+            }
+
+            #[inline(always)]
+            ///Returns underlying channel
+            $vis fn into_inner(self) -> $crate::Channel<$key, $msg, $sender> {
+                self.0
+            }
+        }
+
+        impl ::core::ops::Deref for $name {
+            type Target = $crate::Channel<$key, $msg, $sender>;
+
+            #[inline(always)]
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+    };
+}
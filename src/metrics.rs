@@ -0,0 +1,76 @@
+use core::fmt;
+use core::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::State;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+///Counters of registry, as returned by `Metrics::snapshot`
+pub struct MetricsSnapshot {
+    ///Number of messages accepted by subscribers, refer to `Channel::delivered_count`
+    pub delivered: u64,
+    ///Number of messages dropped due to expired TTL, refer to `Channel::expired_count`
+    pub expired: u64,
+    ///Number of messages discarded due to overflow of bounded queue, refer to `Channel::overflowed_count`
+    pub overflowed: u64,
+    ///Number of discarded messages, lost without reaching dead letter sink, refer to `Channel::overflow_lost_count`
+    pub overflow_lost: u64,
+    ///Number of messages queued, but not yet handled, refer to `Channel::pending`
+    pub pending: usize,
+    ///Number of keyed subscribers, refer to `Channel::subscriber_count_hint`
+    pub subscribers: usize,
+}
+
+///Handle to counters of registry, attached via `RegistryBuilder::metrics`.
+///
+///It can be created and handed over (e.g. to exporter) before registry is built, and it doesn't
+///keep registry alive. All clones observe the same registry.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    state: Arc<Mutex<Weak<State>>>,
+}
+
+impl Metrics {
+    #[inline(always)]
+    ///Creates new handle, which is not attached to any registry yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline(always)]
+    fn lock(&self) -> std::sync::MutexGuard<'_, Weak<State>> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    //Attaches handle to registry, replacing previous one
+    pub(crate) fn attach(&self, state: &Arc<State>) {
+        *self.lock() = Arc::downgrade(state);
+    }
+
+    ///Returns current counters of attached registry.
+    ///
+    ///Returns `None` if registry is not built yet or if registry and all its channels are gone.
+    pub fn snapshot(&self) -> Option<MetricsSnapshot> {
+        let state = self.lock().upgrade()?;
+        Some(MetricsSnapshot {
+            delivered: state.delivered.load(Ordering::Relaxed),
+            expired: state.expired.load(Ordering::Relaxed),
+            overflowed: state.overflowed.load(Ordering::Relaxed),
+            overflow_lost: state.overflow_lost.load(Ordering::Relaxed),
+            pending: state.pending.load(Ordering::Relaxed),
+            subscribers: state.subscribers.load(Ordering::Relaxed),
+        })
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Metrics")
+           .field("attached", &(self.lock().strong_count() > 0))
+           .finish()
+    }
+}
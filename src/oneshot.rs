@@ -0,0 +1,139 @@
+//Single producer, single consumer channel for one value, used to reply on requests
+
+use core::{task, future, time};
+use core::pin::Pin;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use crate::{waker, Cancelled};
+
+///No value yet
+const EMPTY: u8 = 0;
+///Value is written and can be taken by receiver
+const FULL: u8 = 1;
+///Sender is gone without value, or value is already taken
+const CLOSED: u8 = 2;
+///Request is accepted for processing, but no value yet
+const ACCEPTED: u8 = 3;
+///Sender is gone without value after accepting request
+const LOST: u8 = 4;
+
+struct Inner<T> {
+    state: AtomicU8,
+    waker: waker::AtomicWaker,
+    value: UnsafeCell<Option<T>>,
+}
+
+impl<T> Inner<T> {
+    #[inline(always)]
+    fn try_take(&self) -> Option<Result<T, Cancelled>> {
+        match self.state.load(Ordering::Acquire) {
+            EMPTY | ACCEPTED => None,
+            LOST => Some(Err(Cancelled::REPLY_LOST)),
+            FULL => {
+                self.state.store(CLOSED, Ordering::Relaxed);
+                //Sender never touches value after setting FULL, while receiver is unique
+                match unsafe { (*self.value.get()).take() } {
+                    Some(value) => Some(Ok(value)),
+                    None => Some(Err(Cancelled::NOT_RUNNING)),
+                }
+            },
+            _ => Some(Err(Cancelled::NOT_RUNNING)),
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+///Sending side, which closes channel on drop unless value is sent
+pub(crate) struct Sender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Sender<T> {
+    #[inline(always)]
+    //Marks request as accepted, so that dropping sender without value is reported as lost reply
+    pub(crate) fn accept(&self) {
+        let _ = self.inner.state.compare_exchange(EMPTY, ACCEPTED, Ordering::Relaxed, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    pub(crate) fn send(self, value: T) {
+        //Receiver does not access value until FULL is set
+        unsafe {
+            *self.inner.value.get() = Some(value);
+        }
+        self.inner.state.store(FULL, Ordering::Release);
+        //Drop wakes up receiver
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if self.inner.state.compare_exchange(EMPTY, CLOSED, Ordering::Release, Ordering::Relaxed).is_err() {
+            let _ = self.inner.state.compare_exchange(ACCEPTED, LOST, Ordering::Release, Ordering::Relaxed);
+        }
+        self.inner.waker.wake();
+    }
+}
+
+///Receiving side, resolving once value is sent or sender is gone
+pub(crate) struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Receiver<T> {
+    ///Blocks until reply is received, returning `None` on timeout
+    pub(crate) fn wait_timeout(&self, timeout: time::Duration) -> Result<Option<T>, Cancelled> {
+        if let Some(result) = self.inner.try_take() {
+            return result.map(Some);
+        }
+
+        let deadline = std::time::Instant::now() + timeout;
+        self.inner.waker.register_ref(&waker::thread::waker(std::thread::current()));
+        loop {
+            if let Some(result) = self.inner.try_take() {
+                break result.map(Some);
+            }
+
+            match deadline.checked_duration_since(std::time::Instant::now()) {
+                Some(timeout) if !timeout.is_zero() => std::thread::park_timeout(timeout),
+                _ => break Ok(None),
+            }
+        }
+    }
+}
+
+impl<T> future::Future for Receiver<T> {
+    type Output = Result<T, Cancelled>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        if let Some(result) = self.inner.try_take() {
+            return task::Poll::Ready(result);
+        }
+
+        self.inner.waker.register_ref(ctx.waker());
+
+        //Re-check in case value arrived before waker got registered
+        match self.inner.try_take() {
+            Some(result) => task::Poll::Ready(result),
+            None => task::Poll::Pending,
+        }
+    }
+}
+
+pub(crate) fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(Inner {
+        state: AtomicU8::new(EMPTY),
+        waker: waker::AtomicWaker::new(),
+        value: UnsafeCell::new(None),
+    });
+    (Sender {
+        inner: inner.clone(),
+    }, Receiver {
+        inner,
+    })
+}
@@ -0,0 +1,270 @@
+use core::sync::atomic::Ordering;
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+
+use crate::{Envelope, Message, PushErrorKind, Sender, State, TransportSender, TransportReceiver};
+
+struct RingState<K: PartialEq + Eq, T: Send, S: Sender<T>, M> {
+    capacity: usize,
+    queue: VecDeque<Message<K, T, S, M>>,
+    //Discarded messages, waiting to be passed to dead letter sink
+    dropped: VecDeque<T>,
+    keep_dropped: bool,
+    closed: bool,
+}
+
+//Bounded queue, discarding oldest message on overflow
+pub(crate) struct Ring<K: PartialEq + Eq, T: Send, S: Sender<T>, M> {
+    state: Mutex<RingState<K, T, S, M>>,
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Ring<K, T, S, M> {
+    #[inline(always)]
+    fn lock(&self) -> MutexGuard<'_, RingState<K, T, S, M>> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(error) => error.into_inner(),
+        }
+    }
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> RingState<K, T, S, M> {
+    //Discards oldest message, returning `None` if there was none to discard
+    //
+    //Otherwise returns messages, that are no longer kept, and must be dropped by caller outside of lock.
+    fn discard_oldest(&mut self, state: &State) -> Option<Option<T>> {
+        //Only plain messages can be discarded, control operations are always kept
+        let oldest = self.queue.iter().position(|msg| matches!(msg, Message::Msg(..)));
+        match oldest.and_then(|idx| self.queue.remove(idx)) {
+            Some(Message::Msg(_, dropped)) => {
+                state.pending.fetch_sub(1, Ordering::Relaxed);
+                state.overflowed.fetch_add(1, Ordering::Relaxed);
+                match self.keep_dropped {
+                    true => {
+                        let evicted = match self.dropped.len() >= self.capacity {
+                            true => {
+                                state.overflow_lost.fetch_add(1, Ordering::Relaxed);
+                                self.dropped.pop_front()
+                            },
+                            false => None,
+                        };
+                        self.dropped.push_back(dropped);
+                        Some(evicted)
+                    },
+                    false => Some(Some(dropped)),
+                }
+            },
+            _ => None,
+        }
+    }
+}
+
+//Reports empty queue as disconnected, if all `Channel` instances are gone
+//
+//Senders enqueue before being counted as gone, so queue is checked again via `recv`.
+fn check_disconnected<M, F: FnOnce() -> Result<M, mpsc::TryRecvError>>(state: &State, recv: F) -> Result<M, mpsc::TryRecvError> {
+    match state.senders.load(Ordering::Acquire) {
+        0 => match recv() {
+            Err(mpsc::TryRecvError::Empty) => Err(mpsc::TryRecvError::Disconnected),
+            result => result,
+        },
+        _ => Err(mpsc::TryRecvError::Empty),
+    }
+}
+
+//Sending half of registry's inbound queue
+pub(crate) enum Tx<K: PartialEq + Eq, T: Send, S: Sender<T>, M> {
+    Unbounded(mpsc::Sender<Message<K, T, S, M>>),
+    Bounded(Arc<Ring<K, T, S, M>>),
+    Custom(Arc<dyn TransportSender<Envelope<K, T, S, M>>>),
+}
+
+//Receiving half of registry's inbound queue
+pub(crate) enum Rx<K: PartialEq + Eq, T: Send, S: Sender<T>, M> {
+    Unbounded(mpsc::Receiver<Message<K, T, S, M>>),
+    Bounded(Arc<Ring<K, T, S, M>>),
+    Custom(Box<dyn TransportReceiver<Envelope<K, T, S, M>>>),
+}
+
+pub(crate) fn unbounded<K: PartialEq + Eq, T: Send, S: Sender<T>, M>() -> (Tx<K, T, S, M>, Rx<K, T, S, M>) {
+    let (tx, rx) = mpsc::channel();
+    (Tx::Unbounded(tx), Rx::Unbounded(rx))
+}
+
+pub(crate) fn custom<K: PartialEq + Eq, T: Send, S: Sender<T>, M, X: crate::Transport<Envelope<K, T, S, M>>>(transport: X) -> (Tx<K, T, S, M>, Rx<K, T, S, M>) where X::Sender: 'static, X::Receiver: 'static {
+    let (tx, rx) = transport.split();
+    (Tx::Custom(Arc::new(tx)), Rx::Custom(Box::new(rx)))
+}
+
+//Creates queue, which holds at most `capacity` messages, as long as there is message to discard.
+//
+//`keep_dropped` specifies whether discarded messages are passed to registry.
+pub(crate) fn bounded<K: PartialEq + Eq, T: Send, S: Sender<T>, M>(capacity: usize, keep_dropped: bool) -> (Tx<K, T, S, M>, Rx<K, T, S, M>) {
+    let ring = Arc::new(Ring {
+        state: Mutex::new(RingState {
+            capacity,
+            queue: VecDeque::with_capacity(capacity),
+            dropped: VecDeque::new(),
+            keep_dropped,
+            closed: false,
+        }),
+    });
+    (Tx::Bounded(ring.clone()), Rx::Bounded(ring))
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Tx<K, T, S, M> {
+    //Enqueues message, returning it back if registry is gone
+    //
+    //If `reject_full` is set, bounded queue returns message back instead of discarding oldest one.
+    pub(crate) fn send(&self, msg: Message<K, T, S, M>, reject_full: bool, state: &State) -> Result<(), (Message<K, T, S, M>, PushErrorKind)> {
+        match self {
+            Tx::Unbounded(tx) => tx.send(msg).map_err(|error| (error.0, PushErrorKind::Cancelled)),
+            Tx::Bounded(ring) => {
+                let mut ring_state = ring.lock();
+                if ring_state.closed {
+                    return Err((msg, PushErrorKind::Cancelled));
+                }
+
+                let mut discarded = None;
+                if ring_state.queue.len() >= ring_state.capacity {
+                    if reject_full {
+                        return Err((msg, PushErrorKind::Full));
+                    }
+                    discarded = ring_state.discard_oldest(state).flatten();
+                }
+
+                ring_state.queue.push_back(msg);
+                drop(ring_state);
+                //Messages are dropped outside of lock, as they may hold channels
+                drop(discarded);
+                Ok(())
+            },
+            Tx::Custom(tx) => tx.send(Envelope(msg)).map_err(|envelope| (envelope.0, PushErrorKind::Cancelled)),
+        }
+    }
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Tx<K, T, S, M> {
+    //Changes capacity of bounded queue, returning whether queue is bounded
+    //
+    //If queue holds more than `capacity` messages, oldest ones are discarded right away.
+    pub(crate) fn resize(&self, capacity: usize, state: &State) -> bool {
+        match self {
+            Tx::Bounded(ring) => {
+                let mut discarded = Vec::new();
+                let mut ring_state = ring.lock();
+                ring_state.capacity = capacity;
+                while ring_state.queue.len() > capacity {
+                    match ring_state.discard_oldest(state) {
+                        Some(dropped) => discarded.extend(dropped),
+                        None => break,
+                    }
+                }
+                while ring_state.dropped.len() > capacity {
+                    state.overflow_lost.fetch_add(1, Ordering::Relaxed);
+                    discarded.extend(ring_state.dropped.pop_front());
+                }
+                drop(ring_state);
+                //Messages are dropped outside of lock, as they may hold channels
+                drop(discarded);
+                true
+            },
+            Tx::Unbounded(_) | Tx::Custom(_) => false,
+        }
+    }
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Clone for Tx<K, T, S, M> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        match self {
+            Tx::Unbounded(tx) => Tx::Unbounded(tx.clone()),
+            Tx::Bounded(ring) => Tx::Bounded(ring.clone()),
+            Tx::Custom(tx) => Tx::Custom(tx.clone()),
+        }
+    }
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Rx<K, T, S, M> {
+    //Receives next message
+    //
+    //Queue is disconnected once all `Channel` instances are gone, as tracked by `state`, since
+    //`WeakChannel` keeps queue open.
+    pub(crate) fn try_recv(&self, state: &State) -> Result<Message<K, T, S, M>, mpsc::TryRecvError> {
+        match self {
+            Rx::Unbounded(rx) => match rx.try_recv() {
+                Err(mpsc::TryRecvError::Empty) => check_disconnected(state, || rx.try_recv()),
+                result => result,
+            },
+            Rx::Bounded(ring) => {
+                let mut ring_state = ring.lock();
+                match ring_state.queue.pop_front() {
+                    Some(msg) => Ok(msg),
+                    //Senders enqueue under lock before being counted as gone, so no message can be missed
+                    None => match state.senders.load(Ordering::Acquire) {
+                        0 => Err(mpsc::TryRecvError::Disconnected),
+                        _ => Err(mpsc::TryRecvError::Empty),
+                    }
+                }
+            },
+            Rx::Custom(rx) => match rx.try_recv() {
+                Ok(envelope) => Ok(envelope.0),
+                Err(mpsc::TryRecvError::Empty) => check_disconnected(state, || rx.try_recv().map(|envelope| envelope.0)),
+                Err(error) => Err(error),
+            },
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    #[inline(always)]
+    pub(crate) fn is_unbounded(&self) -> bool {
+        matches!(self, Rx::Unbounded(_))
+    }
+
+    #[cfg(feature = "blocking")]
+    //Blocks until next message is received from unbounded queue
+    //
+    //Other queues are reported as disconnected, as they cannot be blocked on.
+    pub(crate) fn recv(&self) -> Result<Message<K, T, S, M>, mpsc::RecvError> {
+        match self {
+            Rx::Unbounded(rx) => rx.recv(),
+            Rx::Bounded(_) | Rx::Custom(_) => Err(mpsc::RecvError),
+        }
+    }
+
+    //Takes messages, discarded due to overflow
+    pub(crate) fn take_dropped(&self) -> VecDeque<T> {
+        match self {
+            Rx::Unbounded(_) | Rx::Custom(_) => VecDeque::new(),
+            Rx::Bounded(ring) => core::mem::take(&mut ring.lock().dropped),
+        }
+    }
+
+    //Stops passing discarded messages to registry
+    pub(crate) fn discard_dropped(&self) {
+        if let Rx::Bounded(ring) = self {
+            let dropped = {
+                let mut ring_state = ring.lock();
+                ring_state.keep_dropped = false;
+                core::mem::take(&mut ring_state.dropped)
+            };
+            //Messages are dropped outside of lock, as they may hold channels
+            drop(dropped);
+        }
+    }
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Drop for Rx<K, T, S, M> {
+    fn drop(&mut self) {
+        if let Rx::Bounded(ring) = self {
+            let (queue, dropped) = {
+                let mut ring_state = ring.lock();
+                ring_state.closed = true;
+                (core::mem::take(&mut ring_state.queue), core::mem::take(&mut ring_state.dropped))
+            };
+            //Messages are dropped outside of lock, as they may hold channels
+            drop(queue);
+            drop(dropped);
+        }
+    }
+}
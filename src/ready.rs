@@ -0,0 +1,77 @@
+use core::task;
+use core::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::Cancelled;
+
+const IDLE: u8 = 0;
+const STARTED: u8 = 1;
+const GONE: u8 = 2;
+
+//Tracks whether registry reached particular state (e.g. started processing messages)
+pub(crate) struct Readiness {
+    state: AtomicU8,
+    wakers: Mutex<Vec<task::Waker>>,
+}
+
+impl Readiness {
+    pub(crate) const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(IDLE),
+            wakers: Mutex::new(Vec::new()),
+        }
+    }
+
+    #[inline(always)]
+    fn wakers(&self) -> MutexGuard<'_, Vec<task::Waker>> {
+        match self.wakers.lock() {
+            Ok(wakers) => wakers,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    #[inline(always)]
+    fn set(&self, state: u8) {
+        if self.state.load(Ordering::Acquire) == IDLE {
+            //State is changed under lock, so that waiter cannot miss it after checking
+            let mut wakers = self.wakers();
+            if self.state.compare_exchange(IDLE, state, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+                for waker in wakers.drain(..) {
+                    waker.wake();
+                }
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn start(&self) {
+        self.set(STARTED)
+    }
+
+    #[inline(always)]
+    pub(crate) fn stop(&self) {
+        self.set(GONE)
+    }
+
+    #[inline(always)]
+    fn result(state: u8) -> task::Poll<Result<(), Cancelled>> {
+        match state {
+            IDLE => task::Poll::Pending,
+            STARTED => task::Poll::Ready(Ok(())),
+            _ => task::Poll::Ready(Err(Cancelled::NOT_RUNNING)),
+        }
+    }
+
+    pub(crate) fn poll(&self, ctx: &mut task::Context<'_>) -> task::Poll<Result<(), Cancelled>> {
+        if let task::Poll::Ready(result) = Self::result(self.state.load(Ordering::Acquire)) {
+            return task::Poll::Ready(result);
+        }
+
+        let mut wakers = self.wakers();
+        let result = Self::result(self.state.load(Ordering::Acquire));
+        if result.is_pending() && !wakers.iter().any(|waker| waker.will_wake(ctx.waker())) {
+            wakers.push(ctx.waker().clone());
+        }
+        result
+    }
+}
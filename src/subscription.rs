@@ -0,0 +1,71 @@
+use core::hash::Hash;
+
+use crate::{Channel, Cancelled, Sender};
+
+///Subscription of a single key, which unsubscribes key once dropped.
+///
+///Unsubscribe on drop is best-effort: it is ignored if registry is no longer running.
+///As it removes whichever channel holds the key at that moment, re-subscribing key elsewhere while
+///subscription is alive gets that channel removed too.
+///
+///Subscription holds `Channel`, hence registry keeps running as long as subscription is alive.
+pub struct Subscription<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M = ()> {
+    //Key is taken out, once subscription is forgotten
+    key: Option<K>,
+    channel: Channel<K, T, S, M>,
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M> Subscription<K, T, S, M> {
+    #[inline(always)]
+    ///Returns subscribed key
+    pub fn key(&self) -> &K {
+        match self.key.as_ref() {
+            Some(key) => key,
+            None => unreachable!(),
+        }
+    }
+
+    #[inline(always)]
+    ///Sends message `msg` to subscribed key.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn send(&self, msg: T) -> Result<(), Cancelled> {
+        self.channel.send_to(self.key().clone(), msg)
+    }
+
+    #[inline(always)]
+    ///Consumes subscription without unsubscribing, returning subscribed key.
+    pub fn forget(mut self) -> K {
+        match self.key.take() {
+            Some(key) => key,
+            None => unreachable!(),
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M> Drop for Subscription<K, T, S, M> {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let _ = self.channel.unsubscribe(key);
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>, M> Channel<K, T, S, M> {
+    #[inline]
+    ///Subscribes provided `channel` with specified `key`, returning guard, which unsubscribes `key` once dropped.
+    ///
+    ///Refer to `Subscription` for details.
+    ///
+    ///Returns `Ok(Subscription)` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn subscribe_scoped(&self, key: K, channel: S) -> Result<Subscription<K, T, S, M>, Cancelled> {
+        self.subscribe(key.clone(), channel)?;
+        Ok(Subscription {
+            key: Some(key),
+            channel: self.clone(),
+        })
+    }
+}
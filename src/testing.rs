@@ -0,0 +1,172 @@
+//!Utilities to test routing of messages
+
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use core::hash::Hash;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{Message, Registry, Sender, SendError, SendErrorKind};
+
+struct Inner<T> {
+    closed: AtomicBool,
+    messages: Mutex<Vec<T>>,
+}
+
+///Sender, collecting messages in order of delivery.
+///
+///All clones share the same storage, so one clone can be subscribed while another is used to
+///inspect delivered messages.
+pub struct CollectingSender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> CollectingSender<T> {
+    #[inline]
+    ///Creates new empty sender
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                closed: AtomicBool::new(false),
+                messages: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    #[inline(always)]
+    fn messages(&self) -> MutexGuard<'_, Vec<T>> {
+        match self.inner.messages.lock() {
+            Ok(messages) => messages,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    #[inline]
+    ///Takes all messages delivered so far, in order of delivery.
+    pub fn drain(&self) -> Vec<T> {
+        core::mem::take(&mut *self.messages())
+    }
+
+    #[inline]
+    ///Returns number of messages delivered and not yet drained
+    pub fn len(&self) -> usize {
+        self.messages().len()
+    }
+
+    #[inline]
+    ///Returns whether there are no messages to drain
+    pub fn is_empty(&self) -> bool {
+        self.messages().is_empty()
+    }
+
+    #[inline]
+    ///Closes sender, making it to reject all further messages as `Closed`.
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::Release);
+    }
+
+    #[inline]
+    ///Returns whether sender is closed
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::Acquire)
+    }
+}
+
+impl<T: Send> Sender<T> for CollectingSender<T> {
+    #[inline]
+    fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.is_closed() {
+            return Err(SendError {
+                kind: SendErrorKind::Closed,
+                message: value,
+            });
+        }
+
+        self.messages().push(value);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn is_closed(&self) -> bool {
+        CollectingSender::is_closed(self)
+    }
+}
+
+impl<T> Clone for CollectingSender<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Default for CollectingSender<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+///Describes what `Registry::step` did
+pub enum StepResult {
+    ///Subscription operation is handled (e.g. `subscribe`, `subscribe_broadcast` or `rekey`)
+    Subscribed,
+    ///Message is handled, regardless of whether there was subscriber to accept it
+    Delivered,
+    ///Removal operation is handled (e.g. `unsubscribe`, `retain` or `prune_closed`)
+    Pruned,
+    ///Query is handled, without changing registry (e.g. `with_keys`)
+    Inspected,
+    ///There is nothing in queue, pending messages (e.g. coalesced) are delivered
+    Empty,
+    ///All channels are gone, registry is finished
+    Disconnected,
+    ///Registry is stopped by its owner
+    Finished,
+}
+
+impl<K: PartialEq + Eq, T: Send, S: Sender<T>, M> Message<K, T, S, M> {
+    fn step(&self) -> StepResult {
+        match self {
+            Message::Subscribe(..) | Message::SubscribeMany(..) | Message::SubscribeBroadcast(..) | Message::SubscribeTap(..) | Message::SubscribeGroup(..) | Message::SubscribeShard(..) | Message::Rekey(..) => StepResult::Subscribed,
+            Message::Unsubscribe(..) | Message::UnsubscribeId(..) | Message::UnsubscribeShard(..) | Message::PruneClosed | Message::Retain(..) | Message::Compact => StepResult::Pruned,
+            Message::IsSubscribed(..) | Message::WithKeys(..) | Message::WithSubscribers(..) => StepResult::Inspected,
+            #[cfg(feature = "blocking")]
+            Message::Wake => StepResult::Inspected,
+            _ => StepResult::Delivered,
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Registry<K, T, S, M> {
+    ///Processes at most one queued operation, returning what is done.
+    ///
+    ///This allows to drive registry deterministically from test, observing its state
+    ///(e.g. via `Channel::subscriber_count_hint` or subscribers) between operations.
+    ///Registry is never woken, so it must be stepped until `Empty` to handle everything sent so far.
+    ///Once `Disconnected` or `Finished` is returned, registry should not be driven anymore.
+    pub fn step(&mut self) -> StepResult {
+        self.state.ready.start();
+        self.dead_letter_dropped();
+        if self.finish_stopped() {
+            return StepResult::Finished;
+        }
+
+        match self.recv_next() {
+            Ok(message) => {
+                let step = message.step();
+                self.handle(message);
+                step
+            },
+            Err(mpsc::TryRecvError::Empty) => {
+                self.flush();
+                StepResult::Empty
+            },
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.finish_disconnected();
+                StepResult::Disconnected
+            },
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use core::fmt;
+use core::hash::Hash;
+
+use crate::{Channel, BoundSender, Sender};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+///Topic name, used as registry key.
+///
+///Wrapping names into `Topic` prevents mixing topic keys with other values of the same type.
+pub struct Topic<N>(pub N);
+
+impl<N> Topic<N> {
+    #[inline(always)]
+    ///Returns topic name
+    pub fn name(&self) -> &N {
+        &self.0
+    }
+
+    #[inline(always)]
+    ///Unwraps topic name
+    pub fn into_name(self) -> N {
+        self.0
+    }
+}
+
+impl<N> From<N> for Topic<N> {
+    #[inline(always)]
+    fn from(name: N) -> Self {
+        Self(name)
+    }
+}
+
+impl<N: fmt::Display> fmt::Display for Topic<N> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, fmt)
+    }
+}
+
+impl<N: PartialEq + Eq + Hash + Clone, T: Send, S: Sender<T>> Channel<Topic<N>, T, S> {
+    #[inline(always)]
+    ///Creates sender bound to topic `name`
+    pub fn topic(&self, name: N) -> BoundSender<Topic<N>, T, S> {
+        self.bind(Topic(name))
+    }
+}
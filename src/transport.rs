@@ -0,0 +1,64 @@
+use std::sync::mpsc;
+
+use crate::{Message, Sender};
+
+///Message, passed to registry via its inbound transport.
+///
+///Its content is only meaningful to the registry.
+pub struct Envelope<K: PartialEq + Eq, T: Send, S: Sender<T>, M = ()>(pub(crate) Message<K, T, S, M>);
+
+///Sending half of the inbound transport, shared by all instances of `Channel`
+pub trait TransportSender<M>: Send + Sync {
+    ///Enqueues `msg`, to be received by registry.
+    ///
+    ///Returns `Err(msg)` if receiving half is gone.
+    fn send(&self, msg: M) -> Result<(), M>;
+}
+
+///Receiving half of the inbound transport, owned by `Registry`.
+pub trait TransportReceiver<M>: Send {
+    ///Receives next message without blocking.
+    ///
+    ///Returns `Err(TryRecvError::Empty)` if there is no message at the moment.
+    ///Returns `Err(TryRecvError::Disconnected)` if there is no message and sending half is dropped.
+    fn try_recv(&self) -> Result<M, mpsc::TryRecvError>;
+}
+
+///Inbound transport of the registry, delivering messages from `Channel` to `Registry`.
+///
+///Registry is woken by `Channel` on its own, so transport only needs to queue messages.
+///
+///Implemented for pair of std mpsc halves, which is what `registry()` uses.
+pub trait Transport<M> {
+    ///Sending half
+    type Sender: TransportSender<M>;
+    ///Receiving half
+    type Receiver: TransportReceiver<M>;
+
+    ///Splits transport into its halves
+    fn split(self) -> (Self::Sender, Self::Receiver);
+}
+
+impl<M: Send> TransportSender<M> for mpsc::Sender<M> {
+    #[inline(always)]
+    fn send(&self, msg: M) -> Result<(), M> {
+        mpsc::Sender::send(self, msg).map_err(|error| error.0)
+    }
+}
+
+impl<M: Send> TransportReceiver<M> for mpsc::Receiver<M> {
+    #[inline(always)]
+    fn try_recv(&self) -> Result<M, mpsc::TryRecvError> {
+        mpsc::Receiver::try_recv(self)
+    }
+}
+
+impl<M: Send> Transport<M> for (mpsc::Sender<M>, mpsc::Receiver<M>) {
+    type Sender = mpsc::Sender<M>;
+    type Receiver = mpsc::Receiver<M>;
+
+    #[inline(always)]
+    fn split(self) -> (Self::Sender, Self::Receiver) {
+        self
+    }
+}
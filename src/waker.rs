@@ -1,6 +1,31 @@
 use core::{ptr, task, hint, mem};
-use core::cell::UnsafeCell;
+#[cfg(not(loom))]
 use core::sync::atomic::{AtomicU8, Ordering};
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicU8, Ordering};
+#[cfg(loom)]
+use loom::cell::UnsafeCell;
+#[cfg(all(feature = "metrics", not(loom)))]
+use core::sync::atomic::AtomicU64;
+#[cfg(all(feature = "metrics", loom))]
+use loom::sync::atomic::AtomicU64;
+
+#[cfg(not(loom))]
+//Mirrors loom's `UnsafeCell`, so that access to the cell can be checked by loom
+struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
+
+#[cfg(not(loom))]
+impl<T> UnsafeCell<T> {
+    #[inline(always)]
+    const fn new(value: T) -> Self {
+        Self(core::cell::UnsafeCell::new(value))
+    }
+
+    #[inline(always)]
+    fn with_mut<R, F: FnOnce(*mut T) -> R>(&self, cb: F) -> R {
+        cb(self.0.get())
+    }
+}
 
 mod noop {
     use core::{ptr, task};
@@ -25,39 +50,51 @@ mod noop {
 
 pub(crate) mod thread {
     use std::thread::Thread;
-    use core::{task, mem};
+    use std::sync::Arc;
+    use core::task;
 
-    const VTABLE: task::RawWakerVTable = task::RawWakerVTable::new(clone, wake, wake_by_ref, on_drop);
+    //Thread is shared via `Arc`, so clones of waker point to the same data and match `will_wake`
+    struct Unpark(Thread);
 
-    unsafe fn on_drop(thread: *const ()) {
-        let thread: Thread = mem::transmute(thread);
-        drop(thread);
-    }
+    impl std::task::Wake for Unpark {
+        #[inline(always)]
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
 
-    unsafe fn clone(thread: *const()) -> task::RawWaker {
-        let thread: Thread = mem::transmute(thread);
-        let new_ptr = mem::transmute(thread.clone());
-        mem::forget(thread);
-        task::RawWaker::new(new_ptr, &VTABLE)
+        #[inline(always)]
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.unpark();
+        }
     }
 
-    unsafe fn wake(thread: *const ()) {
-        let thread: Thread = mem::transmute(thread);
-        thread.unpark();
+    #[inline(always)]
+    pub fn waker(thread: Thread) -> task::Waker {
+        Arc::new(Unpark(thread)).into()
     }
+}
 
-    unsafe fn wake_by_ref(thread: *const ()) {
-        let thread: Thread = mem::transmute(thread);
-        thread.unpark();
-        //wake_by_ref should not consume self
-        mem::forget(thread);
+pub(crate) mod notify {
+    use core::task;
+    use std::sync::Arc;
+
+    struct Notify<F>(F);
+
+    impl<F: Fn() + Send + Sync + 'static> std::task::Wake for Notify<F> {
+        #[inline(always)]
+        fn wake(self: Arc<Self>) {
+            (self.0)()
+        }
+
+        #[inline(always)]
+        fn wake_by_ref(self: &Arc<Self>) {
+            (self.0)()
+        }
     }
 
     #[inline(always)]
-    pub fn waker(thread: Thread) -> task::Waker {
-        unsafe {
-            task::Waker::from_raw(task::RawWaker::new(mem::transmute(thread), &VTABLE))
-        }
+    pub fn waker<F: Fn() + Send + Sync + 'static>(notify: F) -> task::Waker {
+        Arc::new(Notify(notify)).into()
     }
 }
 
@@ -75,6 +112,12 @@ const WAKING: u8 = 0b10;
 pub struct AtomicWaker {
     state: AtomicU8,
     waker: UnsafeCell<task::Waker>,
+    //Number of registrations, that cloned new waker
+    #[cfg(feature = "metrics")]
+    cloned: AtomicU64,
+    //Number of registrations, that kept existing waker as `will_wake` matched
+    #[cfg(feature = "metrics")]
+    reused: AtomicU64,
 }
 
 struct StateRestore<F: Fn()>(F);
@@ -89,8 +132,10 @@ macro_rules! impl_register {
         match $this.state.compare_exchange(WAITING, REGISTERING, Ordering::Acquire, Ordering::Acquire).unwrap_or_else(|err| err) {
             WAITING => {
                 //Make sure we do not stuck in REGISTERING state
+                //Concurrent `wake` may still set `WAKING` bit, hence read-modify-write is used to be
+                //ordered after it
                 let state_guard = StateRestore(|| {
-                    $this.state.store(WAITING, Ordering::Release);
+                    $this.state.swap(WAITING, Ordering::AcqRel);
                 });
 
                 unsafe {
@@ -117,7 +162,7 @@ macro_rules! impl_register {
                             debug_assert_eq!(actual, REGISTERING | WAKING);
 
                             let mut waker = noop::waker();
-                            ptr::swap($this.waker.get(), &mut waker);
+                            $this.waker.with_mut(|cell| ptr::swap(cell, &mut waker));
 
                             // Just restore,
                             // because no one could change state while state == `REGISTERING` | `WAKING`.
@@ -157,9 +202,20 @@ impl AtomicWaker {
         Self {
             state: AtomicU8::new(WAITING),
             waker: UnsafeCell::new(noop::waker()),
+            #[cfg(feature = "metrics")]
+            cloned: AtomicU64::new(0),
+            #[cfg(feature = "metrics")]
+            reused: AtomicU64::new(0),
         }
     }
 
+    #[cfg(feature = "metrics")]
+    #[inline(always)]
+    ///Returns number of registrations, that cloned waker and that reused existing one
+    pub fn registrations(&self) -> (u64, u64) {
+        (self.cloned.load(Ordering::Relaxed), self.reused.load(Ordering::Relaxed))
+    }
+
     /////This is the same function as `register` but working with owned version.
     //pub fn register(&self, waker: task::Waker) {
     //    impl_register!(self(waker) {
@@ -171,14 +227,34 @@ impl AtomicWaker {
     pub fn register_ref(&self, waker: &task::Waker) {
         impl_register!(self(waker) {
             // Lock acquired, update the waker cell
-            if !(*self.waker.get()).will_wake(waker) {
+            self.waker.with_mut(|cell| if !(*cell).will_wake(waker) {
                 let mut waker = waker.clone();
                 //Clone new waker if it is definitely not the same as old one
-                ptr::swap(self.waker.get(), &mut waker);
-            }
+                ptr::swap(cell, &mut waker);
+                #[cfg(feature = "metrics")]
+                self.cloned.fetch_add(1, Ordering::Relaxed);
+            } else {
+                #[cfg(feature = "metrics")]
+                self.reused.fetch_add(1, Ordering::Relaxed);
+            });
         });
     }
 
+    //Replaces registered waker with noop one, without waking it.
+    //
+    //This makes any following `wake` no-op, until new waker is registered.
+    pub fn clear(&self) {
+        if self.state.fetch_or(WAKING, Ordering::AcqRel) == WAITING {
+            let mut waker = noop::waker();
+            self.waker.with_mut(|cell| unsafe {
+                ptr::swap(cell, &mut waker);
+            });
+            self.state.fetch_and(!WAKING, Ordering::Release);
+            drop(waker);
+        }
+        //Otherwise concurrent `wake` takes waker already
+    }
+
     pub fn wake(&self) {
         // AcqRel ordering is used in order to acquire the value of the `task`
         // cell as well as to establish a `release` ordering with whatever
@@ -187,9 +263,9 @@ impl AtomicWaker {
             WAITING => {
                 // The waking lock has been acquired.
                 let mut waker = noop::waker();
-                unsafe {
-                    ptr::swap(self.waker.get(), &mut waker);
-                }
+                self.waker.with_mut(|cell| unsafe {
+                    ptr::swap(cell, &mut waker);
+                });
 
                 // Release the lock
                 self.state.fetch_and(!WAKING, Ordering::Release);
@@ -214,3 +290,85 @@ impl AtomicWaker {
 
 unsafe impl Send for AtomicWaker {}
 unsafe impl Sync for AtomicWaker {}
+
+#[cfg(all(test, loom))]
+mod tests {
+    use super::AtomicWaker;
+
+    use core::task;
+    use loom::sync::Arc;
+    use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    struct CountWaker(AtomicUsize);
+
+    impl std::task::Wake for CountWaker {
+        fn wake(self: std::sync::Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    //Mimics registry, which checks its queue after registering waker
+    fn register_and_check(waker: &AtomicWaker, task_waker: &task::Waker, sent: &AtomicBool) -> bool {
+        waker.register_ref(task_waker);
+        sent.load(Ordering::Acquire)
+    }
+
+    #[test]
+    fn wake_is_never_missed() {
+        loom::model(|| {
+            let waker = Arc::new(AtomicWaker::new());
+            let sent = Arc::new(AtomicBool::new(false));
+            let wakes = std::sync::Arc::new(CountWaker(AtomicUsize::new(0)));
+            let task_waker = task::Waker::from(wakes.clone());
+
+            let sender = {
+                let waker = waker.clone();
+                let sent = sent.clone();
+                loom::thread::spawn(move || {
+                    sent.store(true, Ordering::Release);
+                    waker.wake();
+                })
+            };
+
+            let observed = register_and_check(&waker, &task_waker, &sent);
+            sender.join().expect("Finish successfully");
+            if !observed {
+                assert_eq!(wakes.0.load(Ordering::Relaxed), 1, "Missed wakeup");
+            }
+        });
+    }
+
+    #[test]
+    fn concurrent_wakes_are_never_missed() {
+        loom::model(|| {
+            let waker = Arc::new(AtomicWaker::new());
+            let sent = Arc::new(AtomicUsize::new(0));
+            let wakes = std::sync::Arc::new(CountWaker(AtomicUsize::new(0)));
+            let task_waker = task::Waker::from(wakes.clone());
+
+            let senders = (0..2).map(|_| {
+                let waker = waker.clone();
+                let sent = sent.clone();
+                loom::thread::spawn(move || {
+                    sent.fetch_add(1, Ordering::Release);
+                    waker.wake();
+                })
+            }).collect::<Vec<_>>();
+
+            waker.register_ref(&task_waker);
+            let observed = sent.load(Ordering::Acquire);
+            for sender in senders {
+                sender.join().expect("Finish successfully");
+            }
+            if observed < 2 {
+                assert!(wakes.0.load(Ordering::Relaxed) >= 1, "Missed wakeup");
+            }
+
+            //Waker is consumed on wake, so it needs to be registered again
+            let before = wakes.0.load(Ordering::Relaxed);
+            waker.register_ref(&task_waker);
+            waker.wake();
+            assert_eq!(wakes.0.load(Ordering::Relaxed), before + 1, "Missed wakeup");
+        });
+    }
+}
@@ -0,0 +1,101 @@
+use core::fmt;
+use core::hash::Hash;
+use core::mem::ManuallyDrop;
+use core::sync::atomic::Ordering;
+use std::sync::{mpsc, Arc};
+
+use crate::{queue, Channel, Cancelled, Message, Sender, State};
+
+///Weak handle of registry, which doesn't keep registry running.
+///
+///Registry is disconnected once all `Channel` instances are gone, regardless of weak handles,
+///after which weak handle can no longer be upgraded. Created via `Channel::downgrade`.
+pub struct WeakChannel<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M = ()> {
+    state: Arc<State>,
+    channel: queue::Tx<K, T, S, M>,
+    control: Option<mpsc::Sender<Message<K, T, S, M>>>,
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> WeakChannel<K, T, S, M> {
+    ///Creates `Channel`, if there is still at least one `Channel` alive.
+    ///
+    ///Returned channel keeps registry running, as any other, until it is dropped.
+    pub fn upgrade(&self) -> Option<Channel<K, T, S, M>> {
+        let mut senders = self.state.senders.load(Ordering::Relaxed);
+        loop {
+            //Once last channel is gone, registry is disconnected for good
+            if senders == 0 {
+                break None;
+            }
+
+            match self.state.senders.compare_exchange_weak(senders, senders + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => break Some(Channel {
+                    state: self.state.clone(),
+                    channel: ManuallyDrop::new(self.channel.clone()),
+                    control: ManuallyDrop::new(self.control.clone()),
+                }),
+                Err(actual) => senders = actual,
+            }
+        }
+    }
+
+    #[inline]
+    ///Sends message `msg` to channel with specified `key`, if there is still at least one `Channel` alive.
+    ///
+    ///This is the same as `send_to` via upgraded channel, which is dropped right away, so it never
+    ///prevents registry from finishing.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running or all channels being gone
+    pub fn try_send_to(&self, key: K, msg: T) -> Result<(), Cancelled> {
+        match self.upgrade() {
+            Some(channel) => channel.send_to(key, msg),
+            None => Err(Cancelled::DISCONNECTED),
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Channel<K, T, S, M> {
+    #[inline]
+    ///Creates weak handle, which doesn't keep registry running.
+    ///
+    ///Refer to `WeakChannel` for details.
+    pub fn downgrade(&self) -> WeakChannel<K, T, S, M> {
+        #[cfg(feature = "blocking")]
+        self.state.weak.fetch_add(1, Ordering::AcqRel);
+        WeakChannel {
+            state: self.state.clone(),
+            channel: (*self.channel).clone(),
+            control: (*self.control).clone(),
+        }
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Clone for WeakChannel<K, T, S, M> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        #[cfg(feature = "blocking")]
+        self.state.weak.fetch_add(1, Ordering::AcqRel);
+        Self {
+            state: self.state.clone(),
+            channel: self.channel.clone(),
+            control: self.control.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Drop for WeakChannel<K, T, S, M> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.state.weak.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> fmt::Debug for WeakChannel<K, T, S, M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("WeakChannel")
+           .field("senders", &self.state.senders.load(Ordering::Relaxed))
+           .finish()
+    }
+}
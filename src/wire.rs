@@ -0,0 +1,53 @@
+use core::hash::Hash;
+
+use serde::{Deserialize, Serialize};
+use serde::de::{DeserializeOwned, Deserializer};
+use serde::ser::Serializer;
+
+use crate::{Channel, Cancelled, Sender};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+///Routing operation, that can be sent to registry in other process.
+///
+///Subscriber `S` is local to process, owning registry, so it is never sent: `Subscribe` carries
+///only key and receiving side creates local subscriber for it (e.g. one that forwards messages
+///back over connection, operation came from) when applying operation via `Channel::apply_op`.
+pub enum Op<K, T> {
+    ///Subscribes key, as `Channel::subscribe`
+    Subscribe(K),
+    ///Removes subscriber of key, as `Channel::unsubscribe`
+    Unsubscribe(K),
+    ///Sends message to key, as `Channel::send_to`
+    Msg(K, T),
+}
+
+#[inline(always)]
+///Encodes operation `op` via `serializer`.
+pub fn encode_op<K: Serialize, T: Serialize, S: Serializer>(op: &Op<K, T>, serializer: S) -> Result<S::Ok, S::Error> {
+    op.serialize(serializer)
+}
+
+#[inline(always)]
+///Decodes operation from `deserializer`.
+pub fn decode_op<'de, K: DeserializeOwned, T: DeserializeOwned, D: Deserializer<'de>>(deserializer: D) -> Result<Op<K, T>, D::Error> {
+    Op::deserialize(deserializer)
+}
+
+impl<K: PartialEq + Eq + Hash, T: Send, S: Sender<T>, M> Channel<K, T, S, M> {
+    ///Applies decoded operation `op` to registry.
+    ///
+    ///`subscriber` is invoked only for `Op::Subscribe`, to create local subscriber of the key.
+    ///
+    ///Returns `Ok(())` if registry is still running
+    ///Returns `Err(Cancelled)` if message ignored due to registry not running
+    pub fn apply_op<F: FnOnce(&K) -> S>(&self, op: Op<K, T>, subscriber: F) -> Result<(), Cancelled> {
+        match op {
+            Op::Subscribe(key) => {
+                let channel = subscriber(&key);
+                self.subscribe(key, channel)
+            },
+            Op::Unsubscribe(key) => self.unsubscribe(key),
+            Op::Msg(key, msg) => self.send_to(key, msg),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use core::time;
+use std::sync::mpsc;
+
+use pochta::batching_registry;
+
+#[test]
+fn flush_batch_when_full_or_due() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = batching_registry(3, time::Duration::from_millis(50));
+    let worker = std::thread::spawn(move || {
+        let reason = registry.run();
+        (reason, registry.finish())
+    });
+
+    channel.subscribe(1u8, send).expect("Success");
+    for msg in 0..4 {
+        channel.send_to(1, msg).expect("Success");
+    }
+    channel.send_to(2, 10).expect("Success");
+
+    assert_eq!(recv.recv().expect("Success"), [0, 1, 2]);
+    //Incomplete batch is delivered once delay elapses
+    assert_eq!(recv.recv_timeout(time::Duration::from_secs(5)).expect("Success"), [3]);
+
+    drop(channel);
+    let (reason, subscribers) = worker.join().expect("Finish successfully");
+    assert_eq!(reason.reason(), pochta::CancelReason::Disconnected);
+    assert_eq!(subscribers.len(), 1);
+}
+
+#[test]
+fn flush_batch_only_when_full_without_delay() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = batching_registry(2, time::Duration::MAX);
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe(1u8, send).expect("Success");
+    for msg in 0..3 {
+        channel.send_to(1, msg).expect("Success");
+    }
+    assert_eq!(recv.recv().expect("Success"), [0, 1]);
+    //Incomplete batch is never due
+    assert!(recv.recv_timeout(time::Duration::from_millis(50)).is_err());
+
+    drop(channel);
+    assert_eq!(worker.join().expect("Finish successfully").reason(), pochta::CancelReason::Disconnected);
+    //Pending batch is still flushed on disconnect
+    assert_eq!(recv.recv().expect("Success"), [2]);
+}
+
+#[test]
+fn flush_batch_on_unsubscribe() {
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+    let (channel, registry) = batching_registry(10, time::Duration::from_secs(60));
+
+    channel.subscribe(1u8, send1).expect("Success");
+    channel.subscribe(2u8, send2).expect("Success");
+    channel.send_to(1, "test").expect("Success");
+    channel.send_to(1, "test2").expect("Success");
+    channel.send_to(2, "pending").expect("Success");
+    channel.unsubscribe(1).expect("Success");
+    channel.send_to(1, "nobody").expect("Success");
+
+    //Pending batches are flushed on finish
+    let subscribers = registry.finish();
+    assert_eq!(subscribers.len(), 1);
+    assert_eq!(recv1.try_iter().collect::<Vec<_>>(), [vec!["test", "test2"]]);
+    assert_eq!(recv2.try_iter().collect::<Vec<_>>(), [vec!["pending"]]);
+}
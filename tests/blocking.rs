@@ -0,0 +1,90 @@
+#![cfg(feature = "blocking")]
+
+use core::time;
+use std::sync::mpsc;
+
+use pochta::{registry, CancelReason, RegistryBuilder};
+
+#[test]
+fn run_blocking_until_disconnected() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = RegistryBuilder::new().coalesce().build();
+    let worker = std::thread::spawn(move || {
+        let reason = registry.run();
+        (reason, registry.finish())
+    });
+
+    channel.subscribe(1u8, send).expect("Success");
+    for idx in 0..100 {
+        channel.send_to(1, idx).expect("Success");
+    }
+    //Coalesced messages are delivered before registry blocks
+    let last = recv.iter().find(|idx| *idx == 99);
+    assert_eq!(last, Some(99));
+    for idx in 100..110 {
+        channel.send_to(1, idx).expect("Success");
+        assert_eq!(recv.recv_timeout(time::Duration::from_secs(5)), Ok(idx));
+    }
+
+    drop(channel);
+    let (reason, (subscribers, _)) = worker.join().expect("Finish successfully");
+    assert_eq!(reason.reason(), CancelReason::Disconnected);
+    assert_eq!(subscribers.len(), 1);
+}
+
+#[test]
+fn run_with_stop_handle() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    //Registry cannot block on queue, as it has to be woken to stop
+    let stop = registry.stop_handle();
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe(1u8, send).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    assert_eq!(recv.recv().expect("Success"), 1);
+    stop.stop();
+    assert_eq!(worker.join().expect("Finish successfully").reason(), CancelReason::Finished);
+}
+
+#[test]
+fn weak_channel_wakes_without_evicting() {
+    use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    //Reports itself closed once flag is set
+    struct Closing(Arc<AtomicBool>);
+
+    impl pochta::Sender<u8> for Closing {
+        fn try_send(&self, value: u8) -> Result<(), pochta::SendError<u8>> {
+            Err(pochta::SendError { kind: pochta::SendErrorKind::Closed, message: value })
+        }
+
+        fn is_closed(&self) -> bool {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    let closed = Arc::new(AtomicBool::new(false));
+    let evicted = Arc::new(AtomicUsize::new(0));
+    let on_evict = evicted.clone();
+    let (channel, mut registry) = RegistryBuilder::new().on_evict(move |_| {
+        on_evict.fetch_add(1, Ordering::Relaxed);
+    }).build();
+    let worker = std::thread::spawn(move || {
+        let reason = registry.run();
+        (reason, registry.finish())
+    });
+
+    assert!(channel.subscribe_blocking(1u8, Closing(closed.clone()), time::Duration::from_secs(5)).expect("Success"));
+    let weak = channel.downgrade();
+    closed.store(true, Ordering::Relaxed);
+    //Last channel wakes registry, blocked on queue kept open by weak channel
+    drop(channel);
+    let (reason, (subscribers, _)) = worker.join().expect("Finish successfully");
+    assert_eq!(reason.reason(), CancelReason::Disconnected);
+    //Closed subscriber is not pruned on shutdown
+    assert_eq!(subscribers.len(), 1);
+    assert_eq!(evicted.load(Ordering::Relaxed), 0);
+    drop(weak);
+}
@@ -0,0 +1,284 @@
+mod common;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+
+use pochta::{registry, shared_registry};
+
+#[test]
+fn broadcast_to_group() {
+    const ID: u8 = 1;
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+    let (send3, recv3) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe_broadcast(ID, send1).expect("Success");
+    channel.subscribe_broadcast(ID, send2).expect("Success");
+    channel.subscribe_broadcast(ID, send3).expect("Success");
+    //Regular subscriber is not part of broadcast group
+    channel.send_to(ID, "direct").expect("Success");
+    drop(recv2);
+    channel.broadcast_to(ID, "test").expect("Success");
+    channel.unsubscribe(ID).expect("Success");
+    channel.broadcast_to(ID, "test2").expect("Success");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(recv1.iter().collect::<Vec<_>>(), ["test"]);
+    assert_eq!(recv3.iter().collect::<Vec<_>>(), ["test"]);
+}
+
+#[test]
+fn broadcast_shared() {
+    const ID: u8 = 1;
+    struct NotClone(u32);
+
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+
+    let (channel, mut registry) = shared_registry::<_, NotClone, _>();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe_broadcast(ID, send1).expect("Success");
+    channel.subscribe_broadcast(ID, send2).expect("Success");
+    channel.broadcast_shared_to(ID, NotClone(10)).expect("Success");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    let message1 = recv1.recv().expect("Success");
+    let message2 = recv2.recv().expect("Success");
+    assert_eq!(message1.0, 10);
+    assert!(Arc::ptr_eq(&message1, &message2));
+}
+
+#[test]
+fn broadcast_ack() {
+    const ID: u8 = 1;
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    assert_eq!(common::block_on(channel.broadcast_to_ack(ID, "nobody")).expect("Success"), 0);
+    channel.subscribe_broadcast(ID, send1).expect("Success");
+    channel.subscribe_broadcast(ID, send2).expect("Success");
+    assert_eq!(common::block_on(channel.broadcast_to_ack(ID, "test")).expect("Success"), 2);
+    drop(recv2);
+    assert_eq!(common::block_on(channel.broadcast_to_ack(ID, "test2")).expect("Success"), 1);
+    drop(recv1);
+    assert_eq!(common::block_on(channel.broadcast_to_ack(ID, "test3")).expect("Success"), 0);
+
+    let channel2 = channel.clone();
+    drop(channel);
+    drop(channel2);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn broadcast_to_all() {
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+    let (send3, recv3) = mpsc::channel();
+    let (send4, recv4) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(1u8, send1).expect("Success");
+    channel.subscribe(2u8, send2).expect("Success");
+    channel.subscribe_broadcast(3u8, send3).expect("Success");
+    channel.subscribe(4u8, send4).expect("Success");
+    drop(recv4);
+    channel.broadcast_all("shutdown").expect("Success");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(recv1.iter().collect::<Vec<_>>(), ["shutdown"]);
+    assert_eq!(recv2.iter().collect::<Vec<_>>(), ["shutdown"]);
+    assert_eq!(recv3.iter().collect::<Vec<_>>(), ["shutdown"]);
+}
+
+#[test]
+fn subscribe_all_receives_delivered() {
+    let (send, recv) = mpsc::channel();
+    let (send_all, recv_all) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.subscribe(2, send).expect("Success");
+    channel.subscribe_broadcast(3, mpsc::channel().0).expect("Success");
+    channel.subscribe_all(send_all).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    //Undelivered messages and broadcasts are not passed
+    channel.send_to(4, 4).expect("Success");
+    channel.broadcast_to(3, 3).expect("Success");
+    channel.send_to(2, 2).expect("Success");
+    assert_eq!(recv.recv().expect("Success"), 1);
+    assert_eq!(recv.recv().expect("Success"), 2);
+    assert_eq!(recv_all.recv().expect("Success"), 1);
+    assert_eq!(recv_all.recv().expect("Success"), 2);
+    drop(recv_all);
+
+    //Closed catch-all subscriber is pruned without affecting delivery
+    channel.send_to(1, 5).expect("Success");
+    channel.send_to(2, 6).expect("Success");
+    assert_eq!(recv.recv().expect("Success"), 5);
+    assert_eq!(recv.recv().expect("Success"), 6);
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn subscribe_all_in_delivery_order() {
+    let (send, recv) = mpsc::channel();
+    let (send_all, recv_all) = mpsc::channel();
+    let (channel, registry) = registry();
+
+    channel.subscribe_all(send_all).expect("Success");
+    channel.subscribe(1u8, send).expect("Success");
+    for idx in 0..10 {
+        channel.send_to(1, idx).expect("Success");
+    }
+    drop(registry.finish());
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+    assert_eq!(recv_all.try_iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn subscribe_group_round_robin() {
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+    let (send3, recv3) = mpsc::channel();
+    let (dead, dead_recv) = mpsc::channel();
+    let (channel, mut registry) = pochta::RegistryBuilder::new().dead_letter(dead).build();
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe_group(1u8, send1).expect("Success");
+    channel.subscribe_group(1, send2).expect("Success");
+    channel.subscribe_group(1, send3).expect("Success");
+    for idx in 0..6 {
+        channel.send_to(1, idx).expect("Success");
+    }
+    assert_eq!(recv1.recv().expect("Success"), 0);
+    assert_eq!(recv1.recv().expect("Success"), 3);
+    assert_eq!(recv2.recv().expect("Success"), 1);
+    assert_eq!(recv2.recv().expect("Success"), 4);
+    assert_eq!(recv3.recv().expect("Success"), 2);
+    assert_eq!(recv3.recv().expect("Success"), 5);
+
+    //Closed member is skipped without losing message
+    drop(recv1);
+    for idx in 6..10 {
+        channel.send_to(1, idx).expect("Success");
+    }
+    assert_eq!(recv2.recv().expect("Success"), 6);
+    assert_eq!(recv3.recv().expect("Success"), 7);
+    assert_eq!(recv2.recv().expect("Success"), 8);
+    assert_eq!(recv3.recv().expect("Success"), 9);
+
+    //Once every member is closed, message is undelivered
+    drop(recv2);
+    drop(recv3);
+    channel.send_to(1, 10).expect("Success");
+    assert_eq!(dead_recv.recv().expect("Success"), 10);
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn send_to_selected_keys() {
+    let (send, recv) = mpsc::channel();
+    let (closed, closed_recv) = mpsc::channel();
+    let (dead, dead_recv) = mpsc::channel();
+    let (channel, registry) = pochta::RegistryBuilder::new().dead_letter(dead).build();
+
+    for key in 1u8..=4 {
+        channel.subscribe(key, send.clone()).expect("Success");
+    }
+    channel.subscribe(6, closed).expect("Success");
+    drop(closed_recv);
+    channel.send_to_selected(|keys| {
+        let mut keys = keys.iter().copied().copied().filter(|key| key % 2 == 0).collect::<Vec<_>>();
+        keys.push(9);
+        keys
+    }, "test").expect("Success");
+
+    let (registry, _) = registry.finish();
+    //Closed subscriber is removed
+    assert_eq!(registry.len(), 4);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), ["test", "test"]);
+    //Both vacant and closed keys are undelivered
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), ["test", "test"]);
+}
+
+#[test]
+fn send_to_keys_clones_per_key() {
+    static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct Counted(&'static str);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            CLONES.fetch_add(1, Ordering::Relaxed);
+            Self(self.0)
+        }
+    }
+
+    let (send, recv) = mpsc::channel();
+    let (dead, dead_recv) = mpsc::channel();
+    let (channel, registry) = pochta::RegistryBuilder::new().dead_letter(dead).build();
+
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.subscribe(2u8, send).expect("Success");
+    channel.send_to_keys([1, 9, 2], Counted("test")).expect("Success");
+    channel.send_to_keys(Vec::new(), Counted("none")).expect("Success");
+
+    let (registry, _) = registry.finish();
+    assert_eq!(registry.len(), 2);
+    assert_eq!(recv.try_iter().count(), 2);
+    //Vacant key doesn't prevent delivery to the rest of keys
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), [Counted("test")]);
+    assert_eq!(CLONES.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn send_with_and_result_to_group() {
+    use pochta::SubscriberGone;
+
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe_group(1u8, send1).expect("Success");
+    channel.send_to_with(1, || "lazy").expect("Success");
+    assert_eq!(common::block_on(channel.send_to_result(1, "result")), Ok(()));
+    assert_eq!(recv1.try_iter().collect::<Vec<_>>(), ["lazy", "result"]);
+
+    channel.subscribe_group(2u8, send2).expect("Success");
+    drop(recv2);
+    assert_eq!(common::block_on(channel.send_to_result(2, "closed")), Err(SubscriberGone::Closed));
+    assert_eq!(common::block_on(channel.send_to_result(2, "removed")), Err(SubscriberGone::NoSubscriber));
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
@@ -0,0 +1,627 @@
+mod common;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+
+use pochta::RegistryBuilder;
+
+#[test]
+fn dead_letter_and_evict_hook() {
+    let (dead_send, dead_recv) = mpsc::channel();
+    let (evict_send, evict_recv) = mpsc::channel();
+    let (send, recv) = mpsc::channel();
+    let (group_send, group_recv) = mpsc::channel();
+
+    let (channel, mut registry) = RegistryBuilder::new().capacity(4)
+                                                       .dead_letter(dead_send)
+                                                       .on_evict(move |key: &u8| {
+                                                           let _ = evict_send.send(*key);
+                                                       })
+                                                       .build();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(1, send).expect("Success");
+    channel.subscribe_broadcast(2, group_send).expect("Success");
+    channel.send_to(1, "test").expect("Success");
+    channel.send_to(3, "unknown").expect("Success");
+    assert_eq!(recv.recv().expect("Success"), "test");
+    drop(recv);
+    drop(group_recv);
+    channel.send_to(1, "closed").expect("Success");
+    channel.broadcast_to(2, "closed").expect("Success");
+    channel.send_to(1, "gone").expect("Success");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(dead_recv.iter().collect::<Vec<_>>(), ["unknown", "closed", "gone"]);
+    assert_eq!(evict_recv.iter().collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn poll_budget_yields() {
+    use std::task;
+    use core::pin::Pin;
+    use core::future::Future;
+    use std::sync::Arc;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountWaker(AtomicUsize);
+
+    impl task::Wake for CountWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = RegistryBuilder::new().poll_budget(2).build();
+    let wakes = Arc::new(CountWaker(AtomicUsize::new(0)));
+    let waker = task::Waker::from(wakes.clone());
+    let mut ctx = task::Context::from_waker(&waker);
+
+    channel.subscribe(1u8, send).expect("Success");
+    for idx in 0..3 {
+        channel.send_to(1, idx).expect("Success");
+    }
+
+    //Subscribe and first message
+    assert!(Pin::new(&mut registry).poll(&mut ctx).is_pending());
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [0]);
+    assert_eq!(wakes.0.load(Ordering::Relaxed), 1);
+    assert!(Pin::new(&mut registry).poll(&mut ctx).is_pending());
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(wakes.0.load(Ordering::Relaxed), 2);
+
+    drop(channel);
+    assert!(Pin::new(&mut registry).poll(&mut ctx).is_ready());
+}
+
+#[test]
+fn send_with_fallback_key() {
+    let (dead_send, dead_recv) = mpsc::channel();
+    let (send, recv) = mpsc::channel();
+    let (fallback_send, fallback_recv) = mpsc::channel();
+
+    let (channel, mut registry) = RegistryBuilder::new().dead_letter(dead_send).build();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(1u8, send).expect("Success");
+    channel.subscribe(0u8, fallback_send).expect("Success");
+    channel.send_to_or(1, 0, "primary").expect("Success");
+    channel.send_to_or(2, 0, "vacant").expect("Success");
+    assert_eq!(recv.recv().expect("Success"), "primary");
+    drop(recv);
+    channel.send_to_or(1, 0, "closed").expect("Success");
+    channel.send_to_or(2, 3, "nobody").expect("Success");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(fallback_recv.iter().collect::<Vec<_>>(), ["vacant", "closed"]);
+    assert_eq!(dead_recv.iter().collect::<Vec<_>>(), ["nobody"]);
+}
+
+#[test]
+fn max_subscribers_rejects_new_keys() {
+    let (send, _recv) = mpsc::channel::<u8>();
+    let (channel, mut registry) = RegistryBuilder::new().max_subscribers(2).build();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+        registry.finish()
+    });
+
+    assert!(common::block_on(channel.try_subscribe(1u8, send.clone())).expect("Success"));
+    channel.subscribe(2u8, send.clone()).expect("Success");
+    assert!(!common::block_on(channel.try_subscribe(3u8, send.clone())).expect("Success"));
+    //Existing key can be re-subscribed
+    assert!(common::block_on(channel.try_subscribe(2u8, send.clone())).expect("Success"));
+    channel.unsubscribe(1).expect("Success");
+    assert!(common::block_on(channel.try_subscribe(3u8, send)).expect("Success"));
+
+    drop(channel);
+    let (subscribers, _) = worker.join().expect("Finish successfully");
+    let mut keys = subscribers.keys().copied().collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, [2, 3]);
+}
+
+#[test]
+fn max_keys_per_sender_rejects_new_keys() {
+    use pochta::SubscriberId;
+
+    let (send, _recv) = mpsc::channel::<u8>();
+    let (channel, mut registry) = RegistryBuilder::new().max_keys_per_sender(2).build();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+        registry.finish()
+    });
+
+    assert!(common::block_on(channel.try_subscribe_as(SubscriberId(1), 1u8, send.clone())).expect("Success"));
+    channel.subscribe_as(SubscriberId(1), 2u8, send.clone()).expect("Success");
+    assert!(!common::block_on(channel.try_subscribe_as(SubscriberId(1), 3u8, send.clone())).expect("Success"));
+    //Existing key of the same subscriber can be re-subscribed, while others are not limited
+    assert!(common::block_on(channel.try_subscribe_as(SubscriberId(1), 2u8, send.clone())).expect("Success"));
+    assert!(common::block_on(channel.try_subscribe_as(SubscriberId(2), 3u8, send.clone())).expect("Success"));
+    assert!(common::block_on(channel.try_subscribe(4u8, send.clone())).expect("Success"));
+    channel.unsubscribe(1).expect("Success");
+    assert!(common::block_on(channel.try_subscribe_as(SubscriberId(1), 5u8, send)).expect("Success"));
+
+    drop(channel);
+    let (subscribers, _) = worker.join().expect("Finish successfully");
+    let mut keys = subscribers.keys().copied().collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, [2, 3, 4, 5]);
+}
+
+#[test]
+fn checkpoint_keys_periodically() {
+    use core::time::Duration;
+
+    let (send, _recv) = mpsc::channel::<u8>();
+    let (keys_send, keys_recv) = mpsc::channel();
+    let (channel, mut registry) = RegistryBuilder::new().checkpoint(Duration::from_millis(10), move |keys: &[&u8]| {
+        let mut keys = keys.iter().map(|key| **key).collect::<Vec<_>>();
+        keys.sort();
+        let _ = keys_send.send(keys);
+    }).build();
+    channel.subscribe(2, send.clone()).expect("Success");
+    channel.subscribe(1, send).expect("Success");
+    let worker = std::thread::spawn(move || registry.run());
+
+    //Registry wakes up for checkpoint without any further message
+    while keys_recv.recv_timeout(Duration::from_secs(5)).expect("Success") != [1, 2] {
+    }
+    channel.unsubscribe(2).expect("Success");
+    while keys_recv.recv_timeout(Duration::from_secs(5)).expect("Success") != [1] {
+    }
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn checkpoint_never_fires_for_huge_interval() {
+    use core::time::Duration;
+
+    let (send, recv) = mpsc::channel();
+    let (keys_send, keys_recv) = mpsc::channel::<usize>();
+    let (channel, mut registry) = RegistryBuilder::new().checkpoint(Duration::MAX, move |keys: &[&u8]| {
+        let _ = keys_send.send(keys.len());
+    }).build();
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe(1, send).expect("Success");
+    channel.send_to(1, "test").expect("Success");
+    assert_eq!(recv.recv_timeout(Duration::from_secs(5)).expect("Success"), "test");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+    assert!(keys_recv.try_recv().is_err());
+}
+
+#[test]
+fn send_with_ttl() {
+    use core::time::Duration;
+
+    let (dead_send, dead_recv) = mpsc::channel();
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = RegistryBuilder::new().dead_letter(dead_send).build();
+
+    channel.subscribe(1u8, send).expect("Success");
+    channel.send_to_ttl(1, "expired", Duration::from_secs(0)).expect("Success");
+    channel.send_to_ttl(1, "fresh", Duration::from_secs(3600)).expect("Success");
+    channel.send_to_ttl(1, "forever", Duration::MAX).expect("Success");
+
+    drop(registry.finish());
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), ["fresh", "forever"]);
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), ["expired"]);
+    assert_eq!(channel.expired_count(), 1);
+}
+
+#[test]
+fn drop_oldest_on_overflow() {
+    let (dead_send, dead_recv) = mpsc::channel();
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = RegistryBuilder::new().drop_oldest(2).dead_letter(dead_send).build();
+
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    channel.send_to(1, 2).expect("Success");
+    channel.send_to(1, 3).expect("Success");
+    //Control operations are kept, discarding messages instead
+    channel.subscribe(2u8, send.clone()).expect("Success");
+    channel.subscribe(3u8, send).expect("Success");
+    assert_eq!(channel.pending(), 3);
+    assert_eq!(channel.overflowed_count(), 3);
+    assert_eq!(channel.overflow_lost_count(), 1);
+
+    let (subscribers, _) = registry.finish();
+    assert_eq!(subscribers.len(), 3);
+    assert_eq!(recv.try_iter().count(), 0);
+    //Only latest `capacity` discarded messages are kept for dead letter sink
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), [2, 3]);
+    assert!(channel.send_to(1, 4).is_err());
+}
+
+#[test]
+fn drop_oldest_drops_discarded_outside_of_lock() {
+    type Channel = pochta::Channel<u8, Reentrant, mpsc::Sender<Reentrant>>;
+
+    //Sends to registry once discarded
+    struct Reentrant(Option<Channel>);
+
+    impl Drop for Reentrant {
+        fn drop(&mut self) {
+            if let Some(channel) = self.0.take() {
+                channel.send_to(1, Reentrant(None)).expect("Success");
+            }
+        }
+    }
+
+    let (channel, registry) = RegistryBuilder::new().drop_oldest(1).build();
+    channel.send_to(1, Reentrant(Some(channel.clone()))).expect("Success");
+    channel.send_to(1, Reentrant(None)).expect("Success");
+    assert_eq!(channel.overflowed_count(), 2);
+    assert_eq!(channel.pending(), 1);
+
+    drop(registry.finish());
+}
+
+#[test]
+fn bounded_with_metrics() {
+    use pochta::Metrics;
+
+    let (send, recv) = mpsc::channel();
+    let metrics = Metrics::new();
+    assert!(metrics.snapshot().is_none());
+    let (channel, registry) = RegistryBuilder::new().bounded(1).metrics(metrics.clone()).build();
+
+    channel.subscribe(1u8, send).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    channel.send_to(1, 2).expect("Success");
+    let snapshot = metrics.snapshot().expect("Attached");
+    assert_eq!(snapshot.overflowed, 1);
+    assert_eq!(snapshot.pending, 2);
+
+    let (subscribers, _) = registry.finish();
+    assert_eq!(subscribers.len(), 1);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [2]);
+    let snapshot = metrics.snapshot().expect("Attached");
+    assert_eq!(snapshot.delivered, 1);
+    assert_eq!(snapshot.pending, 0);
+
+    //Handle doesn't keep registry alive
+    drop(subscribers);
+    drop(channel);
+    assert!(metrics.snapshot().is_none());
+}
+
+#[test]
+fn deliver_in_order_of_priority() {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = RegistryBuilder::new().prioritize().build();
+
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.send_to(1, "normal-1").expect("Success");
+    channel.send_to_prio(1, "low", 1).expect("Success");
+    channel.send_to(1, "normal-2").expect("Success");
+    channel.send_to_prio(1, "urgent-1", 10).expect("Success");
+    channel.send_to_prio(1, "urgent-2", 10).expect("Success");
+    //Pending messages are delivered before subscriber is removed
+    channel.unsubscribe(1).expect("Success");
+    channel.send_to_prio(1, "removed", 10).expect("Success");
+
+    let (subscribers, _) = registry.finish();
+    assert!(subscribers.is_empty());
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), ["urgent-1", "urgent-2", "low", "normal-1", "normal-2"]);
+
+    //Without prioritization, priority is ignored
+    let (channel, registry) = pochta::registry();
+    channel.subscribe(1u8, send).expect("Success");
+    channel.send_to(1, "normal").expect("Success");
+    channel.send_to_prio(1, "urgent", 10).expect("Success");
+    drop(registry.finish());
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), ["normal", "urgent"]);
+}
+
+#[test]
+fn push_rejects_when_full() {
+    use pochta::{PushErrorKind, RawMessage};
+
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = RegistryBuilder::new().drop_oldest(2).build();
+
+    channel.push(RawMessage::subscribe(1u8, send)).expect("Success");
+    channel.push(RawMessage::msg(1, 1)).expect("Success");
+    let error = channel.push(RawMessage::msg(1, 2)).unwrap_err();
+    assert_eq!(error.kind, PushErrorKind::Full);
+    assert_eq!(error.to_string(), "queue of registry is full");
+    assert_eq!(channel.overflowed_count(), 0);
+    assert_eq!(channel.pending(), 2);
+
+    let (subscribers, _) = registry.finish();
+    assert_eq!(subscribers.len(), 1);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [1]);
+    let error = channel.push(error.message).unwrap_err();
+    assert_eq!(error.kind, PushErrorKind::Cancelled);
+    assert_eq!(error.to_string(), "registry is not running");
+}
+
+#[test]
+fn drop_oldest_registry_disconnects() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = pochta::registry_bounded_drop_oldest(1);
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe(1u8, send).expect("Success");
+    let channel2 = channel.clone();
+    channel2.send_to(1, "test").expect("Success");
+    assert_eq!(recv.recv().expect("Success"), "test");
+    drop(channel);
+    drop(channel2);
+    assert_eq!(worker.join().expect("Finish successfully").reason(), pochta::CancelReason::Disconnected);
+}
+
+#[test]
+fn resize_inbound_queue() {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = RegistryBuilder::new().drop_oldest(1).build();
+    assert!(!pochta::registry::<u8, u8, mpsc::Sender<u8>>().0.resize_inbound(2));
+
+    channel.subscribe(1u8, send).expect("Success");
+    assert!(channel.resize_inbound(4));
+    for idx in 1..=5 {
+        channel.send_to(1, idx).expect("Success");
+    }
+    assert_eq!(channel.overflowed_count(), 2);
+    //Shrinking discards oldest messages right away
+    assert!(channel.resize_inbound(2));
+    assert_eq!(channel.overflowed_count(), 4);
+    assert_eq!(channel.pending(), 2);
+
+    let (subscribers, _) = registry.finish();
+    assert_eq!(subscribers.len(), 1);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [5]);
+}
+
+#[test]
+fn resize_inbound_drops_discarded_outside_of_lock() {
+    type Channel = pochta::Channel<u8, Reentrant, mpsc::Sender<Reentrant>>;
+
+    //Sends to registry once discarded
+    struct Reentrant(Option<Channel>);
+
+    impl Drop for Reentrant {
+        fn drop(&mut self) {
+            if let Some(channel) = self.0.take() {
+                channel.send_to(1, Reentrant(None)).expect("Success");
+            }
+        }
+    }
+
+    let (channel, registry) = RegistryBuilder::new().drop_oldest(2).build();
+    channel.send_to(1, Reentrant(Some(channel.clone()))).expect("Success");
+    channel.send_to(1, Reentrant(None)).expect("Success");
+    assert!(channel.resize_inbound(1));
+    assert_eq!(channel.overflowed_count(), 2);
+    assert_eq!(channel.pending(), 1);
+
+    drop(registry.finish());
+}
+
+#[test]
+fn priority_control_operations() {
+    let (dead_send, dead_recv) = mpsc::channel();
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = RegistryBuilder::new().priority_control().dead_letter(dead_send).build();
+
+    channel.send_to(1u8, 1).expect("Success");
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.send_to(1u8, 2).expect("Success");
+    channel.unsubscribe(1).expect("Success");
+    channel.subscribe(2u8, send).expect("Success");
+    channel.send_to(2u8, 3).expect("Success");
+
+    let (subscribers, reason) = registry.finish();
+    assert_eq!(reason.reason(), pochta::CancelReason::Finished);
+    assert_eq!(subscribers.len(), 1);
+    assert!(subscribers.contains_key(&2));
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [3]);
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn lifecycle_events() {
+    use pochta::RegistryEvent;
+
+    let (events_send, events_recv) = mpsc::channel();
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = RegistryBuilder::new().events(events_send).build();
+
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.subscribe_broadcast(2u8, send.clone()).expect("Success");
+    channel.unsubscribe(2).expect("Success");
+    channel.unsubscribe(3).expect("Success");
+    channel.rekey(1, 3).expect("Success");
+    channel.subscribe(4u8, send).expect("Success");
+    drop(recv);
+    channel.send_to(4, "closed").expect("Success");
+
+    drop(registry.finish());
+    assert_eq!(events_recv.try_iter().collect::<Vec<_>>(), [
+        RegistryEvent::Subscribed(1),
+        RegistryEvent::Subscribed(2),
+        RegistryEvent::Unsubscribed(2),
+        RegistryEvent::Unsubscribed(1),
+        RegistryEvent::Subscribed(3),
+        RegistryEvent::Subscribed(4),
+        RegistryEvent::Evicted(4),
+    ]);
+}
+
+struct CountingTransport {
+    sent: Arc<AtomicUsize>,
+}
+
+struct CountingSender<M> {
+    sent: Arc<AtomicUsize>,
+    inner: mpsc::Sender<M>,
+}
+
+impl<M: Send> pochta::TransportSender<M> for CountingSender<M> {
+    fn send(&self, msg: M) -> Result<(), M> {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+        self.inner.send(msg).map_err(|error| error.0)
+    }
+}
+
+impl<M: Send> pochta::Transport<M> for CountingTransport {
+    type Sender = CountingSender<M>;
+    type Receiver = mpsc::Receiver<M>;
+
+    fn split(self) -> (Self::Sender, Self::Receiver) {
+        let (inner, recv) = mpsc::channel();
+        (CountingSender { sent: self.sent, inner }, recv)
+    }
+}
+
+#[test]
+fn custom_transport() {
+    let sent = Arc::new(AtomicUsize::new(0));
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = RegistryBuilder::new().transport(CountingTransport { sent: sent.clone() }).build();
+    channel.subscribe(1u8, send).expect("Success");
+    channel.send_to(1, "test").expect("Success");
+    channel.send_to(2, "unknown").expect("Success");
+    assert_eq!(sent.load(Ordering::Relaxed), 3);
+
+    let worker = std::thread::spawn(move || registry.run());
+    assert_eq!(recv.recv().expect("Success"), "test");
+    drop(channel);
+    worker.join().expect("Finish successfully");
+    assert!(recv.recv().is_err());
+}
+
+#[test]
+fn max_inflight_per_key_buffers_overflow() {
+    use core::pin::Pin;
+    use core::future::Future;
+
+    let (dead_send, dead_recv) = mpsc::sync_channel(8);
+    let (send, recv) = mpsc::sync_channel(1);
+    let (channel, mut registry) = RegistryBuilder::new().max_inflight_per_key(2).dead_letter(dead_send).build();
+
+    let waker = pochta::thread_waker(std::thread::current());
+    let mut ctx = core::task::Context::from_waker(&waker);
+
+    channel.subscribe(1u8, send).expect("Success");
+    for idx in 1..=4 {
+        channel.send_to(1, idx).expect("Success");
+    }
+    assert!(Pin::new(&mut registry).poll(&mut ctx).is_pending());
+    assert_eq!(recv.try_recv().expect("Success"), 1);
+    //Message beyond limit is not buffered
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), [4]);
+
+    //Buffered messages are retried once registry is polled again
+    assert!(Pin::new(&mut registry).poll(&mut ctx).is_pending());
+    assert_eq!(recv.try_recv().expect("Success"), 2);
+    channel.send_to(1, 5).expect("Success");
+    assert!(Pin::new(&mut registry).poll(&mut ctx).is_pending());
+    assert_eq!(recv.try_recv().expect("Success"), 3);
+
+    //Messages, still buffered once registry is finished, are not delivered
+    channel.send_to(1, 6).expect("Success");
+    let (subscribers, _) = registry.finish();
+    assert_eq!(subscribers.len(), 1);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [5]);
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), [6]);
+}
+
+#[test]
+fn hooks_on_empty_and_nonempty() {
+    let (events_send, events_recv) = mpsc::channel();
+    let nonempty_send = events_send.clone();
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = RegistryBuilder::new().on_empty(move || {
+                                                        let _ = events_send.send("empty");
+                                                    })
+                                                    .on_nonempty(move || {
+                                                        let _ = nonempty_send.send("nonempty");
+                                                    })
+                                                    .build();
+
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.subscribe(2u8, send).expect("Success");
+    channel.unsubscribe(1).expect("Success");
+    channel.unsubscribe(1).expect("Success");
+    channel.send_to(2, "test").expect("Success");
+    //Closed subscriber is removed on delivery, which empties registry too
+    drop(recv);
+    channel.send_to(2, "closed").expect("Success");
+    channel.subscribe_broadcast(3u8, mpsc::channel().0).expect("Success");
+
+    let (subscribers, _) = registry.finish();
+    assert!(subscribers.is_empty());
+    assert_eq!(events_recv.try_iter().collect::<Vec<_>>(), ["nonempty", "empty"]);
+}
+
+#[test]
+fn middleware_transforms_and_filters() {
+    let (dead_send, dead_recv) = mpsc::channel();
+    let (send, recv) = mpsc::channel();
+    let (send_all, recv_all) = mpsc::channel();
+    let (channel, registry) = RegistryBuilder::new().dead_letter(dead_send)
+                                                    .middleware(|key: &u8, msg: u32| match msg % 2 {
+                                                        0 => Some(msg * 2 + *key as u32),
+                                                        _ => None,
+                                                    })
+                                                    .build();
+
+    channel.subscribe(1u8, send).expect("Success");
+    channel.subscribe_all(send_all).expect("Success");
+    for msg in 0..4 {
+        channel.send_to(1, msg).expect("Success");
+    }
+    //Key without subscriber is not passed to middleware
+    channel.send_to(2, 3).expect("Success");
+
+    let (subscribers, _) = registry.finish();
+    assert_eq!(subscribers.len(), 1);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [1, 5]);
+    assert_eq!(recv_all.try_iter().collect::<Vec<_>>(), [1, 5]);
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), [3]);
+}
+
+#[test]
+fn subscriber_factory_creates_subscriber_on_demand() {
+    let (dead_send, dead_recv) = mpsc::channel();
+    let (send, recv) = mpsc::channel();
+    let created = Arc::new(AtomicUsize::new(0));
+    let counter = created.clone();
+    let (channel, registry) = RegistryBuilder::new().dead_letter(dead_send)
+                                                    .subscriber_factory(move |key: &u8| match *key < 10 {
+                                                        true => {
+                                                            counter.fetch_add(1, Ordering::Relaxed);
+                                                            Some(send.clone())
+                                                        },
+                                                        false => None,
+                                                    })
+                                                    .build();
+
+    channel.send_to(1, "first").expect("Success");
+    channel.send_to(1, "second").expect("Success");
+    channel.send_to(2, "other").expect("Success");
+    channel.send_to(10, "unknown").expect("Success");
+
+    let (subscribers, _) = registry.finish();
+    assert_eq!(subscribers.len(), 2);
+    assert_eq!(created.load(Ordering::Relaxed), 2);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), ["first", "second", "other"]);
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), ["unknown"]);
+}
@@ -0,0 +1,56 @@
+use std::sync::mpsc;
+
+#[test]
+fn deliver_latest_message_per_key() {
+    let (first, first_recv) = mpsc::channel();
+    let (second, second_recv) = mpsc::channel();
+    let (channel, registry) = pochta::coalescing_registry();
+
+    channel.subscribe(1u8, first).expect("Success");
+    channel.subscribe(2u8, second.clone()).expect("Success");
+    for idx in 0..10 {
+        channel.send_to(1, idx).expect("Success");
+        channel.send_to(2, idx * 10).expect("Success");
+    }
+    channel.send_to(1, 100).expect("Success");
+    //Pending messages are delivered before subscriber is removed
+    channel.unsubscribe(2).expect("Success");
+    channel.send_to(2, 200).expect("Success");
+
+    let (subscribers, _) = registry.finish();
+    assert_eq!(subscribers.len(), 1);
+    assert_eq!(first_recv.try_iter().collect::<Vec<_>>(), [100]);
+    assert_eq!(second_recv.try_iter().collect::<Vec<_>>(), [90]);
+    assert_eq!(channel.delivered_count(), 2);
+    drop(second);
+}
+
+#[test]
+fn deliver_newest_first() {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = pochta::lifo_registry();
+
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.subscribe(2u8, send.clone()).expect("Success");
+    for idx in 0..3 {
+        channel.send_to(1, idx).expect("Success");
+        channel.send_to(2, idx * 10).expect("Success");
+    }
+    //Batch is delivered before other operation
+    channel.unsubscribe(2).expect("Success");
+    channel.send_to(1, 100).expect("Success");
+    channel.send_to(2, 200).expect("Success");
+
+    registry.finish();
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [20, 2, 10, 1, 0, 0, 100]);
+
+    let (channel, registry) = pochta::RegistryBuilder::new().lifo_latest().build();
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.subscribe(2u8, send).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    channel.send_to(2, 2).expect("Success");
+    channel.send_to(1, 3).expect("Success");
+
+    registry.finish();
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [3, 2]);
+}
@@ -0,0 +1,15 @@
+use core::task;
+use core::pin::Pin;
+use core::future::Future;
+
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = Box::pin(fut);
+    let waker = pochta::thread_waker(std::thread::current());
+    let mut ctx = task::Context::from_waker(&waker);
+    loop {
+        match Pin::new(&mut fut).as_mut().poll(&mut ctx) {
+            task::Poll::Ready(result) => break result,
+            task::Poll::Pending => std::thread::park(),
+        }
+    }
+}
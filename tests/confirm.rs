@@ -0,0 +1,152 @@
+mod common;
+
+use core::time;
+use std::sync::mpsc;
+
+use pochta::{registry, CancelReason, RequestError, SendOutcome, SubscriberGone};
+
+const TIMEOUT: time::Duration = time::Duration::from_secs(5);
+
+#[test]
+fn send_and_confirm_blocking() {
+    const ID: u8 = 1;
+    let (send, recv) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    assert!(!channel.send_to_confirm_blocking(ID, "nobody", TIMEOUT).expect("Success"));
+    channel.subscribe(ID, send).expect("Success");
+    assert!(channel.send_to_confirm_blocking(ID, "test", TIMEOUT).expect("Success"));
+    assert_eq!(recv.recv().expect("Success"), "test");
+    drop(recv);
+    assert!(!channel.send_to_confirm_blocking(ID, "closed", TIMEOUT).expect("Success"));
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn send_and_confirm_blocking_registry_gone() {
+    let (channel, registry) = registry::<u8, &'static str, mpsc::Sender<&'static str>>();
+    let worker = std::thread::spawn(move || {
+        std::thread::sleep(time::Duration::from_millis(50));
+        drop(registry);
+    });
+
+    channel.send_to_confirm_blocking(1, "test", TIMEOUT).expect_err("Registry to be gone");
+    worker.join().expect("Finish successfully");
+    channel.send_to_confirm_blocking(1, "test", TIMEOUT).expect_err("Registry to be gone");
+}
+
+#[cfg(all(debug_assertions, not(feature = "panic-safe")))]
+#[test]
+fn send_and_confirm_blocking_from_registry_thread() {
+    use pochta::{Channel, Sender, SendError};
+
+    struct Reentrant(Channel<u8, &'static str, Reentrant>);
+
+    impl Sender<&'static str> for Reentrant {
+        fn try_send(&self, value: &'static str) -> Result<(), SendError<&'static str>> {
+            self.0.send_to_confirm_blocking(2, value, TIMEOUT).expect("Success");
+            Ok(())
+        }
+    }
+
+    let (channel, mut registry) = registry();
+    channel.subscribe(1, Reentrant(channel.clone())).expect("Success");
+    channel.send_to(1, "test").expect("Success");
+
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+    worker.join().expect_err("Registry to detect deadlock");
+}
+
+#[test]
+fn subscribe_blocking_before_other_channel_sends() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    let producer = channel.clone();
+    assert!(channel.subscribe_blocking(1u8, send, TIMEOUT).expect("Success"));
+    let producer = std::thread::spawn(move || {
+        assert!(producer.send_to_confirm_blocking(1, "test", TIMEOUT).expect("Success"));
+    });
+    producer.join().expect("Finish successfully");
+    assert_eq!(recv.recv().expect("Success"), "test");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn send_and_get_delivery_result() {
+    const ID: u8 = 1;
+    let (send, recv) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let stop = registry.stop_handle();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    assert_eq!(common::block_on(channel.send_to_result(ID, "nobody")), Err(SubscriberGone::NoSubscriber));
+    channel.subscribe(ID, send).expect("Success");
+    assert_eq!(common::block_on(channel.send_to_result(ID, "test")), Ok(()));
+    assert_eq!(recv.recv().expect("Success"), "test");
+    drop(recv);
+    assert_eq!(common::block_on(channel.send_to_result(ID, "closed")), Err(SubscriberGone::Closed));
+    assert_eq!(common::block_on(channel.send_to_result(ID, "removed")), Err(SubscriberGone::NoSubscriber));
+
+    stop.stop();
+    worker.join().expect("Finish successfully");
+    assert_eq!(common::block_on(channel.send_to_result(ID, "stopped")), Err(SubscriberGone::NotRunning));
+}
+
+#[test]
+fn send_if_present_returns_message_back() {
+    const ID: u8 = 1;
+    let (send, recv) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run());
+
+    assert_eq!(common::block_on(channel.send_to_if_present(ID, "nobody")), Ok(SendOutcome::Vacant("nobody")));
+    channel.subscribe(ID, send).expect("Success");
+    assert_eq!(common::block_on(channel.send_to_if_present(ID, "test")), Ok(SendOutcome::Delivered));
+    assert_eq!(recv.recv().expect("Success"), "test");
+    drop(recv);
+    assert_eq!(common::block_on(channel.send_to_if_present(ID, "closed")), Ok(SendOutcome::Rejected));
+    assert_eq!(common::block_on(channel.send_to_if_present(ID, "removed")), Ok(SendOutcome::Vacant("removed")));
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn tell_lost_reply_from_unprocessed_request() {
+    let (channel, mut registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    //Request taken by registry, which then panics
+    let error = common::block_on(channel.with_keys(|_| panic!("Registry failure"))).expect_err("Registry to be gone");
+    assert_eq!(error.reason(), CancelReason::ReplyLost);
+    assert_eq!(RequestError::from(error), RequestError::ReplyLost);
+    worker.join().expect_err("Registry to panic");
+
+    //Request is never processed
+    let error = common::block_on(channel.retain_ack(|_| true)).expect_err("Registry to be gone");
+    assert_eq!(RequestError::from(error), RequestError::RegistryGone);
+    let (channel, registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+    let reply = channel.clear_ack();
+    drop(registry);
+    assert_eq!(common::block_on(reply).map_err(RequestError::from), Err(RequestError::RegistryGone));
+}
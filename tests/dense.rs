@@ -0,0 +1,62 @@
+mod common;
+
+use core::time;
+use std::sync::mpsc;
+
+use pochta::dense_registry;
+
+#[test]
+fn send_to_dense_registry() {
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+    let (channel, mut registry) = dense_registry(3);
+    let worker = std::thread::spawn(move || {
+        let reason = registry.run();
+        (reason, registry.finish())
+    });
+
+    channel.subscribe(0, send1).expect("Success");
+    channel.subscribe(3, send2.clone()).expect("Success");
+    channel.send_to(0, "test").expect("Success");
+    channel.send_to(3, "test2").expect("Success");
+    //Keys without subscriber, including out of range, are dropped
+    channel.send_to(1, "nobody").expect("Success");
+    channel.send_to(4, "nobody").expect("Success");
+    channel.unsubscribe(0).expect("Success");
+    channel.send_to(0, "test3").expect("Success");
+
+    assert_eq!(recv1.recv().expect("Success"), "test");
+    assert_eq!(recv1.recv_timeout(time::Duration::from_millis(100)), Err(mpsc::RecvTimeoutError::Disconnected));
+    assert_eq!(recv2.recv().expect("Success"), "test2");
+    //Closed subscriber is removed
+    drop(recv2);
+    channel.send_to(3, "closed").expect("Success");
+
+    drop(channel.clone());
+    drop(channel);
+    let (reason, subscribers) = worker.join().expect("Finish successfully");
+    assert_eq!(reason.reason(), pochta::CancelReason::Disconnected);
+    assert_eq!(subscribers.len(), 4);
+    assert!(subscribers.iter().all(Option::is_none));
+    drop(send2);
+}
+
+#[test]
+fn poll_dense_registry() {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = dense_registry::<u32, _>(0);
+    channel.subscribe(0, send).expect("Success");
+    for idx in 0..10 {
+        channel.send_to(0, idx).expect("Success");
+    }
+    drop(channel);
+    assert_eq!(common::block_on(registry).reason(), pochta::CancelReason::Disconnected);
+    assert_eq!(recv.iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+#[should_panic]
+fn subscribe_out_of_range() {
+    let (channel, _registry) = dense_registry::<u32, mpsc::Sender<u32>>(0);
+    let _ = channel.subscribe(1, mpsc::channel().0);
+}
@@ -0,0 +1,50 @@
+use core::time;
+use std::sync::mpsc;
+
+use pochta::{registry, ForwardSender};
+
+const TIMEOUT: time::Duration = time::Duration::from_secs(5);
+
+#[test]
+fn forward_to_another_registry() {
+    let (send, recv) = mpsc::channel();
+
+    let (target, mut target_registry) = registry();
+    let target_worker = std::thread::spawn(move || {
+        target_registry.run();
+    });
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    target.subscribe(2u8, send).expect("Success");
+    channel.subscribe(1u8, ForwardSender::new(target.clone(), 2)).expect("Success");
+    channel.send_to(1, "test").expect("Success");
+    assert_eq!(recv.recv().expect("Success"), "test");
+
+    //Forward sender keeps target running until it is removed
+    channel.unsubscribe(1).expect("Success");
+    drop(target);
+    target_worker.join().expect("Finish successfully");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn forward_to_stopped_registry() {
+    let (target, target_registry) = registry::<u8, &'static str, mpsc::Sender<&'static str>>();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(1u8, ForwardSender::new(target, 2)).expect("Success");
+    drop(target_registry);
+    //Forward sender is pruned once its registry is gone
+    assert!(!channel.send_to_confirm_blocking(1, "test", TIMEOUT).expect("Success"));
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
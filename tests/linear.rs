@@ -0,0 +1,58 @@
+mod common;
+
+use core::time;
+use std::sync::mpsc;
+
+use pochta::linear_registry;
+
+//Key without `Hash`
+#[derive(Debug, PartialEq, Eq)]
+struct Name(&'static str);
+
+#[test]
+fn send_to_linear_registry() {
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+    let (channel, mut registry) = linear_registry();
+    let worker = std::thread::spawn(move || {
+        let reason = registry.run();
+        (reason, registry.finish())
+    });
+
+    channel.subscribe(Name("one"), send1).expect("Success");
+    channel.subscribe(Name("two"), send2.clone()).expect("Success");
+    channel.send_to(Name("one"), "test").expect("Success");
+    channel.send_to(Name("two"), "test2").expect("Success");
+    channel.send_to(Name("three"), "nobody").expect("Success");
+    channel.unsubscribe(Name("one")).expect("Success");
+    channel.send_to(Name("one"), "test3").expect("Success");
+
+    assert_eq!(recv1.recv().expect("Success"), "test");
+    assert_eq!(recv1.recv_timeout(time::Duration::from_millis(100)), Err(mpsc::RecvTimeoutError::Disconnected));
+    assert_eq!(recv2.recv().expect("Success"), "test2");
+    //Closed subscriber is removed
+    drop(recv2);
+    channel.send_to(Name("two"), "closed").expect("Success");
+
+    drop(channel);
+    let (reason, subscribers) = worker.join().expect("Finish successfully");
+    assert_eq!(reason.reason(), pochta::CancelReason::Disconnected);
+    assert!(subscribers.is_empty());
+    drop(send2);
+}
+
+#[test]
+fn poll_linear_registry() {
+    let (send, recv) = mpsc::channel();
+    let (send2, _recv2) = mpsc::channel();
+    let (channel, registry) = linear_registry();
+    channel.subscribe(Name("key"), send2).expect("Success");
+    //Subscribing the same key replaces channel
+    channel.subscribe(Name("key"), send).expect("Success");
+    for idx in 0..10 {
+        channel.send_to(Name("key"), idx).expect("Success");
+    }
+    drop(channel);
+    assert_eq!(common::block_on(registry).reason(), pochta::CancelReason::Disconnected);
+    assert_eq!(recv.iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+}
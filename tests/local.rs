@@ -0,0 +1,74 @@
+#![cfg(feature = "local")]
+
+mod common;
+
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use pochta::{local_registry, LocalChannel, Sender, SendError, SendErrorKind};
+
+#[derive(Clone, Default)]
+struct Collect(Rc<RefCell<Option<Vec<Rc<String>>>>>);
+
+impl Sender<Rc<String>> for Collect {
+    fn try_send(&self, value: Rc<String>) -> Result<(), SendError<Rc<String>>> {
+        match self.0.borrow_mut().as_mut() {
+            Some(messages) => {
+                messages.push(value);
+                Ok(())
+            },
+            None => Err(SendError {
+                kind: SendErrorKind::Closed,
+                message: value,
+            }),
+        }
+    }
+}
+
+#[test]
+fn poll_local_registry() {
+    let collect = Collect(Rc::new(RefCell::new(Some(Vec::new()))));
+    let (channel, registry) = local_registry();
+
+    channel.subscribe(1u8, collect.clone()).expect("Success");
+    channel.send_to(1, Rc::new("test".to_owned())).expect("Success");
+    channel.send_to(2, Rc::new("nobody".to_owned())).expect("Success");
+    channel.clone().send_to(1, Rc::new("test2".to_owned())).expect("Success");
+    drop(channel);
+    assert_eq!(common::block_on(registry).reason(), pochta::CancelReason::Disconnected);
+
+    let messages = collect.0.borrow_mut().take().expect("Messages");
+    assert_eq!(messages.iter().map(|msg| msg.as_str()).collect::<Vec<_>>(), ["test", "test2"]);
+}
+
+type Subscriber = Box<dyn Sender<Rc<String>>>;
+
+struct Relay(LocalChannel<u8, Rc<String>, Subscriber>);
+
+impl Sender<Rc<String>> for Relay {
+    fn try_send(&self, value: Rc<String>) -> Result<(), SendError<Rc<String>>> {
+        self.0.send_to(2, value).map_err(|_| SendError {
+            kind: SendErrorKind::Closed,
+            message: Rc::new(String::new()),
+        })
+    }
+}
+
+#[test]
+fn relay_within_local_registry() {
+    let collect = Collect(Rc::new(RefCell::new(Some(Vec::new()))));
+    let (channel, registry) = local_registry::<u8, Rc<String>, Subscriber>();
+
+    channel.subscribe(2, Box::new(collect.clone())).expect("Success");
+    //Subscriber sends to the same registry while receiving message
+    channel.subscribe(1, Box::new(Relay(channel.clone()))).expect("Success");
+    channel.send_to(1, Rc::new("relayed".to_owned())).expect("Success");
+
+    let subscribers = registry.finish();
+    assert_eq!(subscribers.len(), 2);
+    let messages = collect.0.borrow_mut().take().expect("Messages");
+    assert_eq!(messages.iter().map(|msg| msg.as_str()).collect::<Vec<_>>(), ["relayed"]);
+
+    drop(subscribers);
+    assert_eq!(channel.send_to(2, Rc::new("gone".to_owned())).unwrap_err().reason(), pochta::CancelReason::NotRunning);
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "log")]
+
+use std::sync::{mpsc, Mutex};
+
+use pochta::RegistryBuilder;
+
+static LOGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Logger;
+
+impl log::Log for Logger {
+    fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        LOGS.lock().expect("Lock").push(record.args().to_string());
+    }
+
+    fn flush(&self) {
+    }
+}
+
+#[test]
+fn log_dropped_messages() {
+    log::set_logger(&Logger).expect("Set logger");
+    log::set_max_level(log::LevelFilter::Debug);
+
+    let (channel, mut registry) = RegistryBuilder::<u8, &'static str, mpsc::Sender<&'static str>>::new().log_keys().build();
+    channel.send_to(1, "test").expect("Success");
+    drop(channel);
+    registry.run();
+
+    let (channel, mut registry) = RegistryBuilder::<u8, &'static str, mpsc::Sender<&'static str>>::new().build();
+    channel.send_to(2, "test").expect("Success");
+    drop(channel);
+    registry.run();
+
+    let logs = LOGS.lock().expect("Lock");
+    assert_eq!(*logs, ["pochta: dropped message for unknown key=1", "pochta: dropped message for unknown key"]);
+}
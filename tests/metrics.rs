@@ -0,0 +1,37 @@
+#![cfg(feature = "metrics")]
+
+use core::task;
+use core::future::Future;
+use std::sync::mpsc;
+
+use pochta::registry;
+
+#[test]
+fn count_waker_registrations() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    assert_eq!(channel.waker_stats().cloned, 0);
+
+    let waker = pochta::notify_waker(|| ());
+    channel.subscribe(1u8, send).expect("Success");
+    channel.send_to(1, 0).expect("Success");
+    //Registry re-registers its waker whenever it runs out of messages
+    for _ in 0..3 {
+        assert!(registry.poll_with(&waker).is_pending());
+    }
+    //The same waker is cloned only once
+    let stats = channel.waker_stats();
+    assert_eq!(stats.cloned, 1);
+    assert_eq!(stats.reused, 2);
+
+    for _ in 0..3 {
+        let waker = pochta::notify_waker(|| ());
+        let mut ctx = task::Context::from_waker(&waker);
+        assert!(core::pin::Pin::new(&mut registry).poll(&mut ctx).is_pending());
+    }
+    //New waker on every poll is cloned every time
+    let stats = channel.waker_stats();
+    assert_eq!(stats.cloned, 4);
+    assert_eq!(stats.reused, 2);
+    assert_eq!(recv.try_iter().count(), 1);
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "panic-safe")]
+
+use std::sync::mpsc;
+
+use pochta::{registry, Sender, SendError};
+
+struct Panicking;
+
+impl Sender<&'static str> for Panicking {
+    fn try_send(&self, _: &'static str) -> Result<(), SendError<&'static str>> {
+        panic!("Subscriber panic");
+    }
+}
+
+enum Either {
+    Panicking(Panicking),
+    Channel(mpsc::Sender<&'static str>),
+}
+
+impl Sender<&'static str> for Either {
+    fn try_send(&self, value: &'static str) -> Result<(), SendError<&'static str>> {
+        match self {
+            Either::Panicking(sender) => sender.try_send(value),
+            Either::Channel(sender) => sender.try_send(value),
+        }
+    }
+}
+
+#[test]
+fn should_evict_panicked_subscriber() {
+    let (send, recv) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(1u8, Either::Panicking(Panicking)).expect("Success");
+    channel.subscribe(2u8, Either::Channel(send)).expect("Success");
+    channel.send_to(1, "panic").expect("Success");
+    channel.send_to(2, "test").expect("Success");
+
+    drop(channel);
+    worker.join().expect("Registry to survive panic");
+
+    assert_eq!(recv.iter().collect::<Vec<_>>(), ["test"]);
+}
+
+#[test]
+fn should_evict_panicked_subscriber_with_buffered_messages() {
+    use core::pin::Pin;
+    use core::future::Future;
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    use pochta::{RegistryBuilder, SendErrorKind};
+
+    enum Flaky {
+        //Rejects first message as full, then panics
+        FullThenPanicking(AtomicBool),
+        Channel(mpsc::Sender<&'static str>),
+    }
+
+    impl Sender<&'static str> for Flaky {
+        fn try_send(&self, value: &'static str) -> Result<(), SendError<&'static str>> {
+            match self {
+                Flaky::FullThenPanicking(full) => match full.swap(true, Ordering::Relaxed) {
+                    false => Err(SendError { kind: SendErrorKind::Full, message: value }),
+                    true => panic!("Subscriber panic"),
+                },
+                Flaky::Channel(sender) => sender.try_send(value),
+            }
+        }
+    }
+
+    let (dead_send, dead_recv) = mpsc::channel();
+    let (channel, mut registry) = RegistryBuilder::new().max_inflight_per_key(2).dead_letter(Flaky::Channel(dead_send)).build();
+
+    let waker = pochta::thread_waker(std::thread::current());
+    let mut ctx = core::task::Context::from_waker(&waker);
+
+    channel.subscribe(1u8, Flaky::FullThenPanicking(AtomicBool::new(false))).expect("Success");
+    channel.send_to(1, "buffered").expect("Success");
+    channel.send_to(1, "after panic").expect("Success");
+    assert!(Pin::new(&mut registry).poll(&mut ctx).is_pending());
+    //Buffered message is lost to panic, while following one goes to dead letter
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), ["after panic"]);
+
+    let (subscribers, _) = registry.finish();
+    assert!(subscribers.is_empty());
+}
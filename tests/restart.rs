@@ -0,0 +1,75 @@
+use std::sync::mpsc;
+
+use pochta::{registry, registry_from_map, CancelReason};
+
+#[test]
+fn restart_with_recovered_subscribers() {
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+
+    let (channel, registry) = registry();
+    channel.subscribe(1u8, send1).expect("Success");
+    channel.subscribe(2u8, send2).expect("Success");
+    channel.send_to(1, "before").expect("Success");
+
+    let (subscribers, reason) = registry.finish();
+    assert_eq!(reason.reason(), CancelReason::Finished);
+    assert_eq!(subscribers.len(), 2);
+    assert_eq!(channel.send_to(1, "lost").expect_err("Not running").reason(), CancelReason::NotRunning);
+    drop(recv2);
+
+    let (channel, mut registry) = registry_from_map(subscribers);
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+    channel.send_to(1, "after").expect("Success");
+    assert!(!channel.send_to_confirm_blocking(2, "closed", core::time::Duration::from_secs(5)).expect("Success"));
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(recv1.iter().collect::<Vec<_>>(), ["before", "after"]);
+}
+
+#[test]
+fn finish_disconnected() {
+    let (channel, registry) = registry::<u8, &'static str, mpsc::Sender<&'static str>>();
+    drop(channel);
+    let (subscribers, reason) = registry.finish();
+    assert!(subscribers.is_empty());
+    assert_eq!(reason.reason(), CancelReason::Disconnected);
+}
+
+#[test]
+fn tell_panic_from_shutdown() {
+    let (channel, mut registry) = registry::<u8, u8, mpsc::Sender<u8>>();
+    let stop = registry.stop_handle();
+    let worker = std::thread::spawn(move || registry.run());
+    stop.stop();
+    worker.join().expect("Finish successfully");
+    assert!(!channel.worker_panicked());
+
+    let (channel, mut registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+    let worker = std::thread::spawn(move || registry.run());
+    drop(channel.with_keys(|_| panic!("Registry failure")));
+    worker.join().expect_err("Registry to panic");
+    assert!(channel.worker_panicked());
+    assert_eq!(channel.send_to(1, 1).expect_err("Not running").reason(), CancelReason::NotRunning);
+}
+
+#[test]
+fn restart_supervised_registry_on_panic() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run_supervised(1));
+
+    channel.subscribe(1u8, send).expect("Success");
+    drop(channel.with_keys(|_| panic!("Registry failure")));
+    channel.send_to(1, "after panic").expect("Success");
+    assert_eq!(recv.recv().expect("Success"), "after panic");
+    assert!(channel.worker_panicked());
+
+    //Restarts are exhausted
+    drop(channel.with_keys(|_| panic!("Registry failure")));
+    worker.join().expect_err("Registry to panic");
+}
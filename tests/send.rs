@@ -0,0 +1,88 @@
+use std::sync::{mpsc, Arc};
+
+use pochta::{Channel, ChannelFactory, BatchChannel, BatchRegistry, DenseChannel, DenseRegistry, LinearChannel, LinearRegistry, Registry, RegistryBuilder, BoundSender, ForwardSender, Subscription, WeakChannel};
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn registry_is_send() {
+    assert_send::<Registry<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<Registry<String, Vec<u8>, mpsc::Sender<Vec<u8>>>>();
+    assert_send::<Registry<u64, Arc<String>, mpsc::Sender<Arc<String>>>>();
+    assert_send::<Registry<u8, u8, ForwardSender<u8, u8, mpsc::Sender<u8>>>>();
+    assert_send::<Registry<u8, u8, Box<dyn pochta::Sender<u8> + Send>>>();
+    assert_send::<RegistryBuilder<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<DenseRegistry<&'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<LinearRegistry<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<BatchRegistry<u8, &'static str, mpsc::Sender<Vec<&'static str>>>>();
+}
+
+#[test]
+fn channel_is_send_and_sync() {
+    assert_send::<Channel<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_sync::<Channel<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<WeakChannel<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_sync::<WeakChannel<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<ChannelFactory<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_sync::<ChannelFactory<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<DenseChannel<&'static str, mpsc::Sender<&'static str>>>();
+    assert_sync::<DenseChannel<&'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<LinearChannel<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_sync::<LinearChannel<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<BatchChannel<u8, &'static str, mpsc::Sender<Vec<&'static str>>>>();
+    assert_sync::<BatchChannel<u8, &'static str, mpsc::Sender<Vec<&'static str>>>>();
+    assert_send::<BoundSender<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<Subscription<u8, &'static str, mpsc::Sender<&'static str>>>();
+    assert_send::<ForwardSender<u8, &'static str, mpsc::Sender<&'static str>>>();
+}
+
+#[test]
+fn move_registry_to_worker() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = pochta::registry::<String, String, _>();
+    channel.subscribe("key".to_owned(), send).expect("Success");
+    channel.send_to("key".to_owned(), "test".to_owned()).expect("Success");
+
+    let worker = std::thread::spawn(move || registry.run());
+    assert_eq!(recv.recv().expect("Success"), "test");
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn debug_channel_and_registry() {
+    struct NoDebug;
+
+    let (channel, registry) = pochta::registry::<u8, NoDebug, mpsc::Sender<NoDebug>>();
+    let (send, _recv) = mpsc::channel();
+    channel.subscribe(1, send).expect("Success");
+    let channel2 = channel.clone();
+    assert_eq!(format!("{:?}", channel), "Channel { connected: true, senders: 2, pending: 1 }");
+    drop(channel2);
+
+    let (subscribers, _) = registry.finish();
+    assert_eq!(subscribers.len(), 1);
+    assert_eq!(format!("{:?}", channel), "Channel { connected: false, senders: 1, pending: 0 }");
+
+    let (channel, registry) = pochta::registry_from_map::<u8, NoDebug, _>(subscribers);
+    assert_eq!(format!("{:?}", registry), "Registry { subscribers: 1, broadcast_groups: 0, pending: 0 }");
+    drop(channel);
+}
+
+#[test]
+fn scoped_registry_with_borrowed_channels() {
+    let (send, recv) = mpsc::channel();
+    let prefix = String::from("scoped");
+
+    std::thread::scope(|scope| {
+        let (channel, mut registry) = pochta::registry::<&str, &str, &mpsc::Sender<&str>>();
+        let worker = scope.spawn(move || registry.run());
+
+        channel.subscribe(&prefix, &send).expect("Success");
+        channel.send_to(&prefix, "test").expect("Success");
+        assert_eq!(recv.recv().expect("Success"), "test");
+        drop(channel);
+        worker.join().expect("Finish successfully");
+    });
+}
@@ -0,0 +1,45 @@
+#![cfg(not(loom))]
+
+use pochta::{SendError, SendErrorKind};
+
+struct AsyncSender<T>(smol::channel::Sender<T>);
+
+impl<T: Send> pochta::Sender<T> for AsyncSender<T> {
+    fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        self.0.try_send(value).map_err(|error| SendError {
+            kind: SendErrorKind::Closed,
+            message: error.into_inner(),
+        })
+    }
+}
+
+#[test]
+fn run_registry_as_smol_task() {
+    smol::block_on(async {
+        let (channel, registry) = pochta::registry();
+        let registry = smol::spawn(registry);
+
+        let (send, recv) = smol::channel::unbounded();
+        channel.subscribe(1u8, AsyncSender(send)).expect("Success");
+        for idx in 0..100u32 {
+            channel.send_to(1, idx).expect("Success");
+            //Subscriber task is woken by registry task
+            assert_eq!(recv.recv().await.expect("Success"), idx);
+        }
+
+        let other = channel.clone();
+        let producer = smol::unblock(move || {
+            for idx in 100..200u32 {
+                other.send_to(1, idx).expect("Success");
+            }
+        });
+        for idx in 100..200u32 {
+            assert_eq!(recv.recv().await.expect("Success"), idx);
+        }
+        producer.await;
+
+        drop(channel);
+        assert_eq!(registry.await.reason(), pochta::CancelReason::Disconnected);
+        assert!(recv.recv().await.is_err());
+    });
+}
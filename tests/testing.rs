@@ -0,0 +1,183 @@
+#![cfg(feature = "testing")]
+
+use pochta::registry;
+use pochta::testing::CollectingSender;
+
+#[test]
+fn collect_delivery_order() {
+    let sink = CollectingSender::new();
+    let (channel, registry) = registry();
+
+    channel.send_to(1u8, 0).expect("Success");
+    channel.subscribe(1u8, sink.clone()).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    channel.send_to(1, 2).expect("Success");
+    channel.unsubscribe(1).expect("Success");
+    channel.send_to(1, 3).expect("Success");
+    channel.subscribe(1u8, sink.clone()).expect("Success");
+    channel.send_to(1, 4).expect("Success");
+
+    drop(channel);
+    drop(registry.finish());
+
+    assert_eq!(sink.len(), 3);
+    assert_eq!(sink.drain(), [1, 2, 4]);
+    assert!(sink.is_empty());
+}
+
+#[test]
+fn closed_collecting_sender_is_pruned() {
+    let sink = CollectingSender::new();
+    let (channel, registry) = registry();
+
+    channel.subscribe(1u8, sink.clone()).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    channel.send_to_or(1, 2, 2).expect("Success");
+
+    let (subscribers, _) = registry.finish();
+    assert_eq!(sink.drain(), [1, 2]);
+    assert_eq!(subscribers.len(), 1);
+
+    sink.close();
+    let (channel, registry) = pochta::registry_from_map(subscribers);
+    channel.send_to(1, 3).expect("Success");
+    let (subscribers, _) = registry.finish();
+    assert!(subscribers.is_empty());
+    assert!(sink.is_empty());
+}
+
+#[test]
+fn prune_closed_subscribers() {
+    use core::future::Future;
+    use std::sync::{Arc, Mutex};
+
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let (channel, mut registry) = {
+        let evicted = evicted.clone();
+        pochta::RegistryBuilder::new().on_evict(move |key: &u8| evicted.lock().unwrap().push(*key)).build()
+    };
+    let alive = CollectingSender::<u32>::new();
+    let dead = CollectingSender::new();
+
+    channel.subscribe(1u8, alive.clone()).expect("Success");
+    channel.subscribe(2u8, dead.clone()).expect("Success");
+    channel.subscribe_broadcast(3u8, alive.clone()).expect("Success");
+    channel.subscribe_broadcast(3u8, dead.clone()).expect("Success");
+
+    let waker = pochta::thread_waker(std::thread::current());
+    let mut ctx = std::task::Context::from_waker(&waker);
+    assert!(core::pin::Pin::new(&mut registry).poll(&mut ctx).is_pending());
+    assert!(evicted.lock().unwrap().is_empty());
+
+    dead.close();
+    channel.prune_closed().expect("Success");
+
+    let (subscribers, _) = registry.finish();
+    let mut evicted = evicted.lock().unwrap().clone();
+    evicted.sort();
+    assert_eq!(evicted, [2, 3]);
+    assert_eq!(subscribers.len(), 1);
+    assert!(subscribers.contains_key(&1));
+}
+
+#[test]
+fn closed_sender_is_not_subscribed() {
+    let alive = CollectingSender::<u32>::new();
+    let dead = CollectingSender::new();
+    dead.close();
+    let (channel, registry) = registry();
+
+    channel.subscribe(1u8, alive.clone()).expect("Success");
+    channel.subscribe(1u8, dead.clone()).expect("Success");
+    channel.subscribe_keys([2u8, 3], dead.clone()).expect("Success");
+    channel.subscribe_broadcast(4u8, dead.clone()).expect("Success");
+    channel.subscribe_broadcast(4u8, alive.clone()).expect("Success");
+    channel.broadcast_to(4, 1).expect("Success");
+    channel.send_to(1, 2).expect("Success");
+
+    let (subscribers, _) = registry.finish();
+    assert!(subscribers.is_empty());
+    assert_eq!(alive.drain(), [1]);
+    assert_eq!(channel.delivered_count(), 1);
+}
+
+#[test]
+fn step_registry_one_operation_at_a_time() {
+    use pochta::testing::StepResult;
+
+    let sink = CollectingSender::new();
+    let (channel, mut registry) = registry();
+
+    assert_eq!(registry.step(), StepResult::Empty);
+    channel.subscribe(1u8, sink.clone()).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    channel.prune_closed().expect("Success");
+    channel.unsubscribe(1).expect("Success");
+
+    assert_eq!(registry.step(), StepResult::Subscribed);
+    assert_eq!(channel.subscriber_count_hint(), 1);
+    assert!(sink.is_empty());
+    assert_eq!(registry.step(), StepResult::Delivered);
+    assert_eq!(sink.drain(), [1]);
+    assert_eq!(registry.step(), StepResult::Pruned);
+    assert_eq!(channel.subscriber_count_hint(), 1);
+    assert_eq!(registry.step(), StepResult::Pruned);
+    assert_eq!(channel.subscriber_count_hint(), 0);
+    assert_eq!(registry.step(), StepResult::Empty);
+
+    drop(channel);
+    assert_eq!(registry.step(), StepResult::Disconnected);
+}
+
+#[test]
+fn rekey_reports_replaced_subscriber() {
+    use core::future::Future;
+    use pochta::RegistryEvent;
+    use std::sync::{mpsc, Arc, Mutex};
+
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let (events_send, events_recv) = mpsc::channel();
+    let (channel, mut registry) = {
+        let evicted = evicted.clone();
+        pochta::RegistryBuilder::new().events(events_send)
+                                      .on_evict(move |key: &u8| evicted.lock().unwrap().push(*key))
+                                      .build()
+    };
+    let alive = CollectingSender::new();
+    let dead = CollectingSender::new();
+
+    channel.subscribe(1u8, alive.clone()).expect("Success");
+    channel.subscribe(2u8, alive.clone()).expect("Success");
+    channel.subscribe(3u8, dead.clone()).expect("Success");
+
+    let waker = pochta::thread_waker(std::thread::current());
+    let mut ctx = std::task::Context::from_waker(&waker);
+    assert!(core::pin::Pin::new(&mut registry).poll(&mut ctx).is_pending());
+
+    dead.close();
+    //Replaced subscriber is unsubscribed if alive and evicted if closed
+    channel.rekey(1, 2).expect("Success");
+    channel.rekey(2, 3).expect("Success");
+    channel.send_to(3, "moved").expect("Success");
+    assert!(core::pin::Pin::new(&mut registry).poll(&mut ctx).is_pending());
+
+    let summary = registry.summary();
+    assert_eq!(summary.subscribed, 5);
+    assert_eq!(summary.unsubscribed, 3);
+    let (subscribers, _) = registry.finish();
+    assert_eq!(subscribers.len(), 1);
+    assert!(subscribers.contains_key(&3));
+    assert_eq!(alive.drain(), ["moved"]);
+    assert_eq!(*evicted.lock().unwrap(), [3]);
+    assert_eq!(events_recv.try_iter().collect::<Vec<_>>(), [
+        RegistryEvent::Subscribed(1),
+        RegistryEvent::Subscribed(2),
+        RegistryEvent::Subscribed(3),
+        RegistryEvent::Unsubscribed(1),
+        RegistryEvent::Unsubscribed(2),
+        RegistryEvent::Subscribed(2),
+        RegistryEvent::Unsubscribed(2),
+        RegistryEvent::Evicted(3),
+        RegistryEvent::Subscribed(3),
+    ]);
+}
@@ -0,0 +1,125 @@
+use core::task;
+use core::pin::Pin;
+use core::future::Future;
+use std::sync::mpsc;
+
+use pochta::{registry, thread_waker};
+
+#[test]
+fn should_drive_registry_with_custom_loop() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+
+    let worker = std::thread::spawn(move || {
+        let waker = thread_waker(std::thread::current());
+        let mut ctx = task::Context::from_waker(&waker);
+        let mut polls = 0usize;
+        loop {
+            polls += 1;
+            match Pin::new(&mut registry).poll(&mut ctx) {
+                task::Poll::Ready(_) => break polls,
+                task::Poll::Pending => std::thread::park(),
+            }
+        }
+    });
+
+    channel.subscribe(1u8, send).expect("Success");
+    channel.send_to(1, "test").expect("Success");
+    assert_eq!(recv.recv().expect("Success"), "test");
+
+    drop(channel);
+    let polls = worker.join().expect("Finish successfully");
+    assert!(polls >= 1);
+}
+
+#[test]
+fn should_clone_and_wake() {
+    let waker = thread_waker(std::thread::current());
+    let cloned = waker.clone();
+    //Clones share the same thread, so registry keeps its waker instead of replacing it
+    assert!(waker.will_wake(&waker.clone()));
+    cloned.wake_by_ref();
+    cloned.wake();
+    //Token is set by wake, so park returns immediately
+    std::thread::park();
+    drop(waker);
+}
+
+#[test]
+fn should_poll_registry_with_pinned_types() {
+    use core::marker::PhantomPinned;
+
+    #[derive(PartialEq, Eq, Hash)]
+    struct Key(u8, PhantomPinned);
+
+    struct PinnedSender(mpsc::Sender<&'static str>, PhantomPinned);
+
+    impl pochta::Sender<&'static str> for PinnedSender {
+        fn try_send(&self, value: &'static str) -> Result<(), pochta::SendError<&'static str>> {
+            self.0.try_send(value)
+        }
+    }
+
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let waker = thread_waker(std::thread::current());
+    let mut ctx = task::Context::from_waker(&waker);
+
+    channel.subscribe(Key(1, PhantomPinned), PinnedSender(send, PhantomPinned)).expect("Success");
+    channel.send_to(Key(1, PhantomPinned), "test").expect("Success");
+    assert!(Pin::new(&mut registry).poll(&mut ctx).is_pending());
+    assert_eq!(recv.try_recv().expect("Success"), "test");
+
+    drop(channel);
+    assert!(Pin::new(&mut registry).poll(&mut ctx).is_ready());
+}
+
+#[test]
+fn should_drive_registry_with_notifier() {
+    use std::sync::Arc;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let notified = Arc::new(AtomicUsize::new(0));
+    let waker = {
+        let notified = notified.clone();
+        pochta::notify_waker(move || {
+            notified.fetch_add(1, Ordering::Relaxed);
+        })
+    };
+
+    assert!(registry.poll_with(&waker).is_pending());
+    channel.subscribe(1u8, send).expect("Success");
+    assert!(notified.load(Ordering::Relaxed) >= 1);
+    channel.send_to(1, "test").expect("Success");
+    assert!(registry.poll_with(&waker).is_pending());
+    assert_eq!(recv.try_recv().expect("Success"), "test");
+
+    drop(channel);
+    assert!(registry.poll_with(&waker).is_ready());
+}
+
+#[test]
+fn observe_registry_len_between_polls() {
+    let (send, _recv) = mpsc::channel::<&'static str>();
+    let (channel, registry) = registry();
+    let mut registry = Box::pin(registry);
+    let waker = thread_waker(std::thread::current());
+    let mut ctx = task::Context::from_waker(&waker);
+
+    assert!(registry.as_mut().poll(&mut ctx).is_pending());
+    assert!(registry.is_empty());
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.subscribe(2u8, send.clone()).expect("Success");
+    //Not yet handled until polled
+    assert_eq!(registry.len(), 0);
+    assert!(registry.as_mut().poll(&mut ctx).is_pending());
+    assert_eq!(registry.len(), 2);
+    channel.unsubscribe(1).expect("Success");
+    assert!(registry.as_mut().poll(&mut ctx).is_pending());
+    assert_eq!(registry.len(), 1);
+
+    drop(channel);
+    assert!(registry.as_mut().poll(&mut ctx).is_ready());
+}
@@ -0,0 +1,28 @@
+#![cfg(feature = "topic")]
+
+use std::sync::mpsc;
+
+use pochta::{registry, Topic};
+
+#[test]
+fn send_to_topic() {
+    let (send, recv) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(Topic("news"), send).expect("Success");
+    let news = channel.topic("news");
+    assert_eq!(news.key(), &Topic("news"));
+    news.send("test1").expect("Success");
+    news.clone().send("test2").expect("Success");
+    channel.topic("weather").send("ignored").expect("Success");
+
+    drop(news);
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(recv.iter().collect::<Vec<_>>(), ["test1", "test2"]);
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{mpsc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span;
+
+use pochta::RegistryBuilder;
+
+#[derive(Default)]
+struct Fields(Vec<String>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn core::fmt::Debug) {
+        self.0.push(format!("{}={:?}", field.name(), value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push(format!("{}={}", field.name(), value));
+    }
+}
+
+#[derive(Default)]
+struct Spans {
+    spans: Mutex<Vec<Vec<String>>>,
+}
+
+impl tracing::Subscriber for Spans {
+    fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        let mut spans = self.spans.lock().expect("Lock");
+        spans.push(fields.0);
+        span::Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, id: &span::Id, values: &span::Record<'_>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        let mut spans = self.spans.lock().expect("Lock");
+        spans[id.into_u64() as usize - 1].extend(fields.0);
+    }
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {
+    }
+
+    fn event(&self, _: &tracing::Event<'_>) {
+    }
+
+    fn enter(&self, _: &span::Id) {
+    }
+
+    fn exit(&self, _: &span::Id) {
+    }
+}
+
+#[test]
+fn span_per_message() {
+    let spans = std::sync::Arc::new(Spans::default());
+    let dispatch = tracing::Dispatch::from(spans.clone());
+
+    tracing::dispatcher::with_default(&dispatch, || {
+        let (send, _recv) = mpsc::channel();
+        let (channel, registry) = RegistryBuilder::<u8, u8, mpsc::Sender<u8>>::new().trace_keys().build();
+        channel.subscribe(1, send).expect("Success");
+        channel.send_to(1, 1).expect("Success");
+        channel.compact().expect("Success");
+        drop(registry.finish());
+
+        let (channel, registry) = RegistryBuilder::<u8, u8, mpsc::Sender<u8>>::new().build();
+        channel.send_to(1, 1).expect("Success");
+        drop(registry.finish());
+    });
+
+    let spans = spans.spans.lock().expect("Lock");
+    assert_eq!(*spans, [
+        vec!["operation=subscribe".to_owned(), "key=1".to_owned()],
+        vec!["operation=msg".to_owned(), "key=1".to_owned()],
+        vec!["operation=compact".to_owned()],
+        vec!["operation=msg".to_owned()],
+    ]);
+}
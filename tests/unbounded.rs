@@ -1,7 +1,9 @@
+mod common;
+
 use core::time;
 use std::sync::mpsc;
 
-use pochta::registry;
+use pochta::{registry, CancelReason, RawMessage, SubscriberId};
 
 #[test]
 fn send_and_unsubscribe() {
@@ -29,3 +31,878 @@ fn send_and_unsubscribe() {
 
     worker.join().expect("Finish successfully");
 }
+
+#[test]
+fn cancel_reason() {
+    let (channel, mut registry) = registry::<u8, &'static str, mpsc::Sender<&'static str>>();
+    let worker = std::thread::spawn(move || {
+        registry.run()
+    });
+
+    drop(channel);
+    let error = worker.join().expect("Finish successfully");
+    assert_eq!(error.reason(), CancelReason::Disconnected);
+
+    let (channel, worker) = pochta::registry::<u8, &'static str, mpsc::Sender<&'static str>>();
+    drop(worker);
+    let error = channel.send_to(1, "test").expect_err("Registry is not running");
+    assert_eq!(error.reason(), CancelReason::NotRunning);
+    assert_eq!(error.to_string(), "Cancelled: registry is not running");
+}
+
+#[test]
+fn unsubscribe_by_sender_id() {
+    const CLIENT: SubscriberId = SubscriberId(1);
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe_as(CLIENT, 1u8, send1.clone()).expect("Success");
+    channel.subscribe_as(CLIENT, 2u8, send1).expect("Success");
+    channel.subscribe(3u8, send2).expect("Success");
+    channel.unsubscribe_sender(CLIENT).expect("Success");
+    channel.send_to(1, "test1").expect("Success");
+    channel.send_to(2, "test2").expect("Success");
+    channel.send_to(3, "test3").expect("Success");
+
+    //All clones of sender are dropped after unsubscribe
+    let message = recv1.recv_timeout(time::Duration::from_millis(100));
+    assert_eq!(message, Err(mpsc::RecvTimeoutError::Disconnected));
+    assert_eq!(recv2.recv().expect("Success"), "test3");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn compact_keeps_subscribers() {
+    let (send, recv) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    for key in 0u8..64 {
+        channel.subscribe(key, send.clone()).expect("Success");
+    }
+    channel.subscribe_broadcast(64u8, send.clone()).expect("Success");
+    channel.subscribe_group(65u8, send).expect("Success");
+    for key in 1u8..64 {
+        channel.unsubscribe(key).expect("Success");
+    }
+    channel.compact().expect("Success");
+    channel.send_to(0, 0).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    channel.broadcast_to(64, 64).expect("Success");
+    channel.send_to(65, 65).expect("Success");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(recv.iter().collect::<Vec<_>>(), [0, 64, 65]);
+}
+
+#[test]
+fn send_via_bound_sender() {
+    const ID: u8 = 1;
+    let (send, recv) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(ID, send).expect("Success");
+    let bound = channel.bind(ID);
+    bound.send("test1").expect("Success");
+    //Dropping bound sender keeps subscription
+    drop(bound.clone());
+    bound.send("test2").expect("Success");
+    bound.unsubscribe().expect("Success");
+    channel.send_to(ID, "test3").expect("Success");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(recv.iter().collect::<Vec<_>>(), ["test1", "test2"]);
+}
+
+#[test]
+fn unsubscribe_scoped_on_drop() {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = registry();
+
+    let subscription = channel.subscribe_scoped(1u8, send.clone()).expect("Success");
+    subscription.send("test1").expect("Success");
+    drop(subscription);
+    channel.send_to(1, "test2").expect("Success");
+
+    let subscription = channel.subscribe_scoped(2u8, send).expect("Success");
+    assert_eq!(subscription.forget(), 2);
+    channel.send_to(2, "test3").expect("Success");
+    let subscription = channel.subscribe_scoped(3u8, mpsc::channel().0).expect("Success");
+
+    let (subscribers, _) = registry.finish();
+    let mut keys = subscribers.keys().copied().collect::<Vec<_>>();
+    keys.sort_unstable();
+    assert_eq!(keys, [2, 3]);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), ["test1", "test3"]);
+
+    //Unsubscribe is ignored once registry is gone
+    drop(subscription);
+    assert!(channel.subscribe_scoped(4u8, mpsc::channel().0).is_err());
+}
+
+#[test]
+fn subscribe_many_keys() {
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(3u8, send2).expect("Success");
+    //Overwrites existing subscriber of 3
+    channel.subscribe_keys([1, 2, 3], send1).expect("Success");
+    channel.send_to(1, "test1").expect("Success");
+    channel.send_to(2, "test2").expect("Success");
+    channel.send_to(3, "test3").expect("Success");
+    channel.send_to(4, "test4").expect("Success");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(recv1.iter().collect::<Vec<_>>(), ["test1", "test2", "test3"]);
+    assert_eq!(recv2.iter().count(), 0);
+}
+
+#[test]
+fn send_lazily_created_message() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let (send, recv) = mpsc::channel();
+    let created = Arc::new(AtomicUsize::new(0));
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(1u8, send).expect("Success");
+    for key in [1, 2] {
+        let created = created.clone();
+        channel.send_to_with(key, move || {
+            created.fetch_add(1, Ordering::Relaxed);
+            "test"
+        }).expect("Success");
+    }
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(recv.iter().collect::<Vec<_>>(), ["test"]);
+    assert_eq!(created.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn every_key_is_usable() {
+    let (send, recv) = mpsc::channel();
+
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    for key in u8::MIN..=u8::MAX {
+        channel.subscribe(key, send.clone()).expect("Success");
+    }
+    drop(send);
+    //Control operations in between must not affect any key
+    channel.compact().expect("Success");
+    channel.unsubscribe_sender(SubscriberId(0)).expect("Success");
+    for key in u8::MIN..=u8::MAX {
+        channel.send_to(key, key).expect("Success");
+    }
+    channel.unsubscribe_sender(SubscriberId(u8::MAX as u64)).expect("Success");
+    for key in u8::MIN..=u8::MAX {
+        channel.unsubscribe(key).expect("Success");
+    }
+
+    let messages = recv.iter().collect::<Vec<_>>();
+    assert_eq!(messages, (u8::MIN..=u8::MAX).collect::<Vec<_>>());
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn send_backpressured() {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = registry();
+
+    channel.subscribe(1u8, send).expect("Success");
+    assert_eq!(channel.send_to_backpressured(1, 1).expect("Success"), 2);
+    assert_eq!(channel.send_to_backpressured(1, 2).expect("Success"), 3);
+    assert_eq!(channel.pending(), 3);
+
+    drop(registry.finish());
+    assert_eq!(channel.pending(), 0);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [1, 2]);
+    //Registry is gone, so failed message is not counted
+    assert!(channel.send_to_backpressured(1, 3).is_err());
+    assert_eq!(channel.pending(), 0);
+}
+
+#[test]
+fn rekey_subscriber() {
+    let (send, recv) = mpsc::channel();
+    let (other, other_recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+        registry.finish()
+    });
+
+    channel.subscribe(1u8, send).expect("Success");
+    channel.subscribe(2u8, other).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    channel.rekey(1, 2).expect("Success");
+    channel.send_to(1, 2).expect("Success");
+    channel.send_to(2, 3).expect("Success");
+    assert!(!common::block_on(channel.rekey_ack(1, 3)).expect("Success"));
+    assert!(common::block_on(channel.rekey_ack(2, 3)).expect("Success"));
+    channel.send_to(3, 4).expect("Success");
+
+    drop(channel);
+    let (subscribers, _) = worker.join().expect("Finish successfully");
+    assert_eq!(subscribers.keys().collect::<Vec<_>>(), [&3]);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [1, 3, 4]);
+    assert_eq!(other_recv.try_iter().count(), 0);
+}
+
+#[test]
+fn send_raw_messages() {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = registry();
+
+    let messages = vec![
+        RawMessage::subscribe(1u8, send),
+        RawMessage::msg(1, 1),
+        RawMessage::unsubscribe(1),
+        RawMessage::msg(1, 2),
+    ];
+    for message in messages {
+        channel.send_message(message).expect("Success");
+    }
+
+    let (subscribers, _) = registry.finish();
+    assert!(subscribers.is_empty());
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [1]);
+}
+
+#[test]
+fn wait_registry_ready() {
+    let (channel, mut registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+    let ready = channel.wait_ready();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+    common::block_on(ready).expect("Success");
+    //Once started, it resolves right away
+    common::block_on(channel.wait_ready()).expect("Success");
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    let (channel, registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+    let ready = channel.wait_ready();
+    drop(registry);
+    assert_eq!(common::block_on(ready).unwrap_err().reason(), CancelReason::NotRunning);
+}
+
+#[test]
+fn convert_mpsc_send_error() {
+    let (send, recv) = mpsc::channel();
+    drop(recv);
+
+    let error: pochta::SendError<u8> = send.send(1).unwrap_err().into();
+    assert!(error.kind.is_closed());
+    assert_eq!(error.message, 1);
+}
+
+#[test]
+fn route_vacant_keys_to_shard() {
+    let (exact, exact_recv) = mpsc::channel();
+    let (shard, shard_recv) = mpsc::channel();
+    let (other, other_recv) = mpsc::channel();
+    let (channel, registry) = registry();
+
+    let key = (0u32..).find(|key| pochta::shard_of(key, 2) == 1).expect("Key of shard");
+    //Shard 0 of 4 is within shard 0 of 2, which has no subscriber
+    let other_key = (0u32..).find(|key| pochta::shard_of(key, 4) == 0).expect("Key of shard");
+    channel.subscribe_shard(1, 2, shard).expect("Success");
+    channel.subscribe_shard(0, 4, other).expect("Success");
+    channel.send_to(key, 1).expect("Success");
+    channel.subscribe(key, exact).expect("Success");
+    //Exact subscriber takes precedence
+    channel.send_to(key, 2).expect("Success");
+    channel.send_to(other_key, 3).expect("Success");
+    channel.unsubscribe_shard(1, 2).expect("Success");
+    channel.unsubscribe(key).expect("Success");
+    channel.send_to(key, 4).expect("Success");
+
+    drop(registry.finish());
+    assert_eq!(exact_recv.try_iter().collect::<Vec<_>>(), [2]);
+    assert_eq!(shard_recv.try_iter().collect::<Vec<_>>(), [1]);
+    assert_eq!(other_recv.try_iter().collect::<Vec<_>>(), [3]);
+}
+
+#[test]
+fn stop_registry_with_alive_channels() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let stop = registry.stop_handle();
+    let worker = std::thread::spawn(move || {
+        let reason = registry.run();
+        (reason, registry.finish())
+    });
+
+    channel.subscribe(1u8, send).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    assert_eq!(recv.recv().expect("Success"), 1);
+    stop.stop();
+    assert_eq!(channel.send_to(1, 2).unwrap_err().reason(), CancelReason::NotRunning);
+
+    let (reason, (subscribers, _)) = worker.join().expect("Finish successfully");
+    assert_eq!(reason.reason(), CancelReason::Finished);
+    assert_eq!(subscribers.len(), 1);
+    assert!(recv.try_recv().is_err());
+}
+
+#[test]
+fn summary_of_run() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.subscribe_broadcast(2u8, send).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    channel.broadcast_to(2, 2).expect("Success");
+    channel.send_to(3, 3).expect("Success");
+    channel.unsubscribe(1).expect("Success");
+    channel.unsubscribe(1).expect("Success");
+    channel.send_to(1, 4).expect("Success");
+    drop(channel);
+
+    assert_eq!(registry.run().reason(), CancelReason::Disconnected);
+    let summary = registry.summary();
+    assert_eq!(summary.subscribed, 2);
+    assert_eq!(summary.unsubscribed, 1);
+    assert_eq!(summary.delivered, 2);
+    assert_eq!(summary.dropped, 2);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn subscribe_dyn_senders() {
+    use std::sync::{Arc, Mutex};
+
+    struct Collect(Mutex<Vec<u8>>);
+
+    impl pochta::Sender<u8> for Collect {
+        fn try_send(&self, value: u8) -> Result<(), pochta::SendError<u8>> {
+            self.0.lock().unwrap().push(value);
+            Ok(())
+        }
+    }
+
+    let (send, recv) = mpsc::channel();
+    let collect = Arc::new(Collect(Mutex::new(Vec::new())));
+    let (channel, registry) = pochta::registry::<u8, u8, Box<dyn pochta::Sender<u8> + Send>>();
+
+    channel.subscribe(1, Box::new(send)).expect("Success");
+    channel.subscribe(2, Box::new(collect.clone())).expect("Success");
+    channel.broadcast_all(1).expect("Success");
+    channel.send_to(2, 2).expect("Success");
+
+    drop(registry.finish());
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [1]);
+    assert_eq!(*collect.0.lock().unwrap(), [1, 2]);
+}
+
+#[test]
+fn compare_registry_ids() {
+    let (channel, registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+    let (other, other_registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+
+    assert_eq!(channel.registry_id(), registry.id());
+    assert_eq!(channel.clone().registry_id(), channel.registry_id());
+    assert_eq!(other.registry_id(), other_registry.id());
+    assert_ne!(channel.registry_id(), other.registry_id());
+}
+
+#[test]
+fn spawn_and_stop_actor() {
+    let (result, results) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    let mut sum = 0;
+    let actor = channel.spawn_actor(1u8, move |value: u32| {
+        sum += value;
+        result.send(sum).expect("Success");
+    }).expect("Success");
+    assert_eq!(*actor.key(), 1);
+    actor.send(1).expect("Success");
+    channel.send_to(1, 2).expect("Success");
+    assert_eq!(results.recv().expect("Success"), 1);
+    assert_eq!(results.recv().expect("Success"), 3);
+
+    actor.stop().expect("Finish successfully");
+    channel.send_to(1, 3).expect("Success");
+    drop(channel);
+    worker.join().expect("Finish successfully");
+    assert!(results.recv().is_err());
+}
+
+#[test]
+fn concurrent_senders_never_lose_wakeup() {
+    const SENDERS: usize = 4;
+    const MESSAGES: usize = 10_000;
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    channel.subscribe(1u8, send).expect("Success");
+    let worker = std::thread::spawn(move || registry.run());
+
+    let senders = (0..SENDERS).map(|_| {
+        let channel = channel.clone();
+        std::thread::spawn(move || for idx in 0..MESSAGES {
+            channel.send_to(1, idx).expect("Success");
+            if idx % 1000 == 0 {
+                std::thread::yield_now();
+            }
+        })
+    }).collect::<Vec<_>>();
+    for sender in senders {
+        sender.join().expect("Finish successfully");
+    }
+
+    for _ in 0..SENDERS * MESSAGES {
+        recv.recv_timeout(core::time::Duration::from_secs(5)).expect("Success");
+    }
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn pump_from_receiver() {
+    let (send, recv) = mpsc::channel();
+    let (input, input_recv) = mpsc::channel();
+    let (channel, registry) = registry();
+    channel.subscribe(1u8, send).expect("Success");
+
+    let pump = {
+        let channel = channel.clone();
+        std::thread::spawn(move || {
+            let result = channel.pump_from(&input_recv);
+            (result, input_recv)
+        })
+    };
+    input.send((1, "first")).expect("Success");
+    input.send((2, "unknown")).expect("Success");
+    input.send((1, "second")).expect("Success");
+    drop(input);
+    let (result, _) = pump.join().expect("Finish successfully");
+    assert_eq!(result, Ok(3));
+
+    let (input, input_recv) = mpsc::channel();
+    input.send((1, "third")).expect("Success");
+    input.send((1, "remaining")).expect("Success");
+    let (subscribers, _) = registry.finish();
+    assert_eq!(channel.pump_from(&input_recv), Err((1, "third")));
+    assert_eq!(input_recv.try_iter().collect::<Vec<_>>(), [(1, "remaining")]);
+    assert_eq!(subscribers.len(), 1);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), ["first", "second"]);
+}
+
+#[test]
+fn run_closure_over_keys() {
+    let (send, _recv) = mpsc::channel::<u8>();
+    let (keys_send, keys_recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe(String::from("first"), send.clone()).expect("Success");
+    channel.subscribe(String::from("second"), send.clone()).expect("Success");
+    channel.subscribe_broadcast(String::from("group"), send).expect("Success");
+    common::block_on(channel.with_keys(move |keys| {
+        let mut keys = keys.map(|key| key.len()).collect::<Vec<_>>();
+        keys.sort();
+        keys_send.send(keys).expect("Success");
+    })).expect("Success");
+    assert_eq!(keys_recv.try_recv().expect("Success"), [5, 6]);
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn retain_subscribers_by_key() {
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = registry();
+
+    for key in 1u8..=6 {
+        channel.subscribe(key, send.clone()).expect("Success");
+    }
+    channel.subscribe_broadcast(7, send.clone()).expect("Success");
+    channel.subscribe_broadcast(8, send).expect("Success");
+    channel.retain(|key| key % 2 == 0).expect("Success");
+    for key in 1u8..=6 {
+        channel.send_to(key, key).expect("Success");
+    }
+    channel.broadcast_to(7, 7).expect("Success");
+    channel.broadcast_to(8, 8).expect("Success");
+
+    let (subscribers, _) = registry.finish();
+    let mut keys = subscribers.keys().copied().collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, [2, 4, 6]);
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [2, 4, 6, 8]);
+}
+
+#[test]
+fn create_channels_via_factory() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let factory = channel.into_factory();
+    let worker = std::thread::spawn(move || registry.run());
+
+    let channel = factory.channel();
+    channel.subscribe(1u8, send).expect("Success");
+    drop(channel);
+    //Factory keeps registry running without channels
+    factory.channel().send_to(1, "test").expect("Success");
+    assert_eq!(recv.recv().expect("Success"), "test");
+
+    drop(factory);
+    assert_eq!(worker.join().expect("Finish successfully").reason(), pochta::CancelReason::Disconnected);
+}
+
+#[test]
+fn join_registry_on_shutdown() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe(1u8, send).expect("Success");
+    for idx in 0..100 {
+        channel.send_to(1, idx).expect("Success");
+    }
+    common::block_on(channel.join());
+    //Registry has handled everything before finishing
+    assert_eq!(recv.iter().count(), 100);
+    assert_eq!(worker.join().expect("Finish successfully").reason(), pochta::CancelReason::Disconnected);
+
+    let (channel, registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+    drop(registry);
+    common::block_on(channel.join());
+}
+
+#[test]
+fn send_before_unsubscribe_is_delivered() {
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run());
+
+    //Registry may go idle between iterations, so messages cross wake up boundary
+    for idx in 0..100u32 {
+        let (send, recv) = mpsc::channel();
+        channel.subscribe(idx, send).expect("Success");
+        channel.send_to(idx, idx).expect("Success");
+        channel.unsubscribe(idx).expect("Success");
+
+        assert_eq!(recv.recv(), Ok(idx));
+        //Sender is dropped once unsubscribed
+        assert_eq!(recv.recv(), Err(mpsc::RecvError));
+    }
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    //Buffered messages are delivered before unsubscribe too
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = pochta::RegistryBuilder::new().prioritize().build();
+    let worker = std::thread::spawn(move || registry.run());
+    for idx in 0..100u32 {
+        channel.subscribe(idx, send.clone()).expect("Success");
+        channel.send_to_prio(idx, idx, 1).expect("Success");
+        channel.unsubscribe(idx).expect("Success");
+    }
+    drop(send);
+    assert_eq!(recv.iter().collect::<Vec<_>>(), (0..100).collect::<Vec<_>>());
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn count_removed_subscribers() {
+    let (send, _recv) = mpsc::channel::<u8>();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run());
+
+    for key in 1u8..=6 {
+        channel.subscribe(key, send.clone()).expect("Success");
+    }
+    channel.subscribe_broadcast(2, send.clone()).expect("Success");
+    channel.subscribe_broadcast(7, send).expect("Success");
+    assert_eq!(common::block_on(channel.retain_ack(|key| key % 2 != 0)), Ok(4));
+    assert_eq!(common::block_on(channel.retain_ack(|key| key % 2 != 0)), Ok(0));
+    assert_eq!(common::block_on(channel.rekey_ack(1, 2)), Ok(true));
+    assert_eq!(common::block_on(channel.clear_ack()), Ok(4));
+    channel.clear().expect("Success");
+
+    drop(channel);
+    let reason = worker.join().expect("Finish successfully");
+    assert_eq!(reason.reason(), CancelReason::Disconnected);
+}
+
+#[test]
+fn count_delivered_messages() {
+    let (send, recv) = mpsc::channel();
+    let (closed, _) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run());
+
+    assert_eq!(channel.delivered_count(), 0);
+    channel.subscribe(1u8, send.clone()).expect("Success");
+    channel.subscribe(2u8, closed).expect("Success");
+    channel.subscribe_broadcast(3u8, send.clone()).expect("Success");
+    channel.subscribe_broadcast(3u8, send.clone()).expect("Success");
+    channel.subscribe_group(4u8, send.clone()).expect("Success");
+    channel.subscribe_group(4u8, send).expect("Success");
+
+    channel.send_to(1, 1).expect("Success");
+    //Failed deliveries are not counted
+    channel.send_to(2, 2).expect("Success");
+    channel.send_to(5, 5).expect("Success");
+    //Every broadcast subscriber is counted, while group message is delivered once
+    assert_eq!(common::block_on(channel.broadcast_to_ack(3, 3)), Ok(2));
+    assert!(channel.send_to_confirm_blocking(4, 4, time::Duration::from_secs(1)).expect("Success"));
+    assert_eq!(channel.delivered_count(), 4);
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+    assert_eq!(recv.try_iter().collect::<Vec<_>>(), [1, 3, 3, 4]);
+}
+
+#[test]
+fn check_sender_is_subscribed() {
+    const CLIENT: SubscriberId = SubscriberId(1);
+    let (send1, recv1) = mpsc::channel::<u8>();
+    let (send2, _recv2) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run());
+
+    assert_eq!(common::block_on(channel.is_subscribed(CLIENT)), Ok(false));
+    channel.subscribe_as(CLIENT, 1u8, send1.clone()).expect("Success");
+    channel.subscribe_as(CLIENT, 2u8, send1).expect("Success");
+    assert_eq!(common::block_on(channel.is_subscribed(CLIENT)), Ok(true));
+    //Overwritten by another subscriber
+    channel.subscribe_as(SubscriberId(2), 1, send2.clone()).expect("Success");
+    assert_eq!(common::block_on(channel.is_subscribed(CLIENT)), Ok(true));
+    //Evicted on failed delivery
+    drop(recv1);
+    channel.send_to(2, 2).expect("Success");
+    assert_eq!(common::block_on(channel.is_subscribed(CLIENT)), Ok(false));
+    assert_eq!(common::block_on(channel.is_subscribed(SubscriberId(2))), Ok(true));
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn operations_of_handle_are_ordered() {
+    let (send, recv) = mpsc::channel();
+    let (dead, dead_recv) = mpsc::channel();
+    let (channel, mut registry) = pochta::RegistryBuilder::new().dead_letter(dead).build();
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.send_to(1u8, 0u32).expect("Success");
+    channel.subscribe(1, send).expect("Success");
+    channel.send_to(1, 1).expect("Success");
+    channel.send_to(1, 2).expect("Success");
+    channel.rekey(1, 2).expect("Success");
+    channel.send_to(1, 3).expect("Success");
+    channel.send_to(2, 4).expect("Success");
+    //Other handle is ordered after operations above, as long as it is used after them
+    let other = channel.clone();
+    std::thread::spawn(move || {
+        other.send_to(2, 5).expect("Success");
+    }).join().expect("Success");
+    channel.unsubscribe(2).expect("Success");
+    channel.send_to(2, 6).expect("Success");
+
+    //Subscriber is dropped once unsubscribed, ending iteration
+    assert_eq!(recv.iter().collect::<Vec<_>>(), [1, 2, 4, 5]);
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+    assert_eq!(dead_recv.try_iter().collect::<Vec<_>>(), [0, 3, 6]);
+}
+
+#[test]
+fn count_strong_refs_on_teardown() {
+    let (channel, mut registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+    assert_eq!(channel.strong_refs(), 2);
+    let stop = registry.stop_handle();
+    let other = channel.clone();
+    assert_eq!(channel.strong_refs(), 4);
+    drop(other);
+
+    let worker = std::thread::spawn(move || registry.run());
+    stop.stop();
+    worker.join().expect("Finish successfully");
+    //Only channel and stop handle are left once worker is joined
+    assert_eq!(channel.strong_refs(), 2);
+    drop(stop);
+    assert_eq!(channel.strong_refs(), 1);
+}
+
+#[test]
+fn spawn_registry_on_configured_thread() {
+    let (send, recv) = mpsc::channel();
+    let builder = std::thread::Builder::new().name("registry".to_owned()).stack_size(256 * 1024);
+    let (channel, worker) = pochta::registry_spawned_with(builder).expect("Spawn thread");
+    assert_eq!(worker.thread().name(), Some("registry"));
+
+    channel.subscribe(1u8, send).expect("Success");
+    channel.send_to(1, "test").expect("Success");
+    assert_eq!(recv.recv(), Ok("test"));
+
+    drop(channel);
+    assert_eq!(worker.join().expect("Finish successfully").reason(), CancelReason::Disconnected);
+
+    let (channel, worker) = pochta::registry_spawned::<u8, u8, mpsc::Sender<u8>>();
+    assert_eq!(worker.thread().name(), Some("pochta"));
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn drop_channels_after_worker_exit() {
+    use core::pin::Pin;
+    use core::future::Future;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::task;
+    use std::sync::Arc;
+
+    let (channel, mut registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+    let other = channel.clone();
+    let stop = registry.stop_handle();
+    let worker = std::thread::spawn(move || registry.run());
+    stop.stop();
+    worker.join().expect("Finish successfully");
+    //Late wake ups after worker exit are harmless
+    assert!(channel.send_to(1, 1).is_err());
+    drop(other);
+    drop(channel);
+
+    struct CountWaker(AtomicUsize);
+
+    impl task::Wake for CountWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let wakes = Arc::new(CountWaker(AtomicUsize::new(0)));
+    let waker = task::Waker::from(wakes.clone());
+    let mut ctx = task::Context::from_waker(&waker);
+    let (channel, mut registry) = pochta::registry::<u8, u8, mpsc::Sender<u8>>();
+    assert!(Pin::new(&mut registry).poll(&mut ctx).is_pending());
+    drop(waker);
+    assert_eq!(Arc::strong_count(&wakes), 2);
+
+    //Dropped registry releases task's waker, so remaining channels no longer wake it
+    drop(registry);
+    assert_eq!(Arc::strong_count(&wakes), 1);
+    assert!(channel.send_to(1, 1).is_err());
+    drop(channel);
+    assert_eq!(wakes.0.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn weak_channel_does_not_keep_registry() {
+    let (send, recv) = mpsc::channel();
+    let (channel, mut registry) = registry();
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe(1u8, send).expect("Success");
+    let weak = channel.downgrade();
+    weak.try_send_to(1, "test").expect("Success");
+    assert_eq!(recv.recv(), Ok("test"));
+
+    //Transient upgrade keeps detection of last channel
+    let upgraded = weak.upgrade().expect("Upgrade");
+    drop(channel);
+    upgraded.send_to(1, "upgraded").expect("Success");
+    drop(upgraded);
+
+    assert_eq!(worker.join().expect("Finish successfully").reason(), CancelReason::Disconnected);
+    assert_eq!(recv.iter().collect::<Vec<_>>(), ["upgraded"]);
+    assert!(weak.upgrade().is_none());
+    assert_eq!(weak.clone().try_send_to(1, "gone").map_err(|error| error.reason()), Err(CancelReason::Disconnected));
+}
+
+#[test]
+fn hint_subscriber_count() {
+    let (send, _recv) = mpsc::channel::<u8>();
+    let mut subscribers = std::collections::HashMap::new();
+    subscribers.insert(1u8, send.clone());
+    let (channel, mut registry) = pochta::registry_from_map(subscribers);
+    assert_eq!(channel.subscriber_count_hint(), 1);
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe(2, send.clone()).expect("Success");
+    channel.subscribe_broadcast(3, send).expect("Success");
+    common::block_on(channel.with_keys(|_| ())).expect("Success");
+    assert_eq!(channel.subscriber_count_hint(), 2);
+
+    channel.clear().expect("Success");
+    common::block_on(channel.with_keys(|_| ())).expect("Success");
+    assert_eq!(channel.subscriber_count_hint(), 0);
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
+
+#[test]
+fn inspect_subscribers_with_meta() {
+    let (send, _recv) = mpsc::channel::<u8>();
+    let (found_send, found_recv) = mpsc::channel();
+    let (channel, mut registry) = pochta::registry_with_meta::<u8, u8, _, &'static str>();
+    let worker = std::thread::spawn(move || registry.run());
+
+    channel.subscribe_with_meta(1, send.clone(), "fast").expect("Success");
+    channel.subscribe_with_meta(2, send.clone(), "slow").expect("Success");
+    channel.subscribe(3, send.clone()).expect("Success");
+    channel.rekey(2, 4).expect("Success");
+    common::block_on(channel.with_subscribers(move |subscribers| {
+        let mut found = subscribers.map(|(key, _, meta)| (*key, meta.copied())).collect::<Vec<_>>();
+        found.sort();
+        found_send.send(found).expect("Success");
+    })).expect("Success");
+    assert_eq!(found_recv.try_recv().expect("Success"), [(1, Some("fast")), (3, None), (4, Some("slow"))]);
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+}
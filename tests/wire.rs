@@ -0,0 +1,25 @@
+#![cfg(feature = "serde")]
+
+use std::sync::mpsc;
+
+use pochta::{decode_op, encode_op, registry, Op};
+
+#[test]
+fn apply_decoded_ops() {
+    let ops = [Op::Subscribe(1u8), Op::Msg(1, "test".to_owned()), Op::Unsubscribe(1), Op::Msg(1, "gone".to_owned())];
+    let encoded = ops.iter().map(|op| encode_op(op, serde_json::value::Serializer).expect("Encode")).collect::<Vec<_>>();
+    assert_eq!(encoded[1], serde_json::json!({"Msg": [1, "test"]}));
+
+    let (send, recv) = mpsc::channel();
+    let (channel, registry) = registry();
+    for op in encoded {
+        let op = decode_op::<u8, String, _>(op).expect("Decode");
+        //Remote side only names key, while subscriber is local
+        channel.apply_op(op, |_| send.clone()).expect("Success");
+    }
+    drop(send);
+
+    let (subscribers, _) = registry.finish();
+    assert!(subscribers.is_empty());
+    assert_eq!(recv.iter().collect::<Vec<_>>(), ["test"]);
+}
@@ -0,0 +1,64 @@
+use std::sync::mpsc;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use pochta::registry;
+
+static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, PartialEq)]
+struct Ping;
+
+impl Clone for Ping {
+    fn clone(&self) -> Self {
+        CLONES.fetch_add(1, Ordering::Relaxed);
+        Ping
+    }
+}
+
+#[test]
+fn signal_with_unit_message() {
+    let (send, recv) = mpsc::channel();
+
+    let (channel, mut registry) = registry::<u32, (), mpsc::Sender<()>>();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(1, send).expect("Success");
+    for _ in 0..3 {
+        channel.send_to(1, ()).expect("Success");
+    }
+    channel.send_to(2, ()).expect("Success");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(recv.iter().count(), 3);
+}
+
+#[test]
+fn signal_without_spurious_clones() {
+    let (send1, recv1) = mpsc::channel();
+    let (send2, recv2) = mpsc::channel();
+    let (send3, recv3) = mpsc::channel();
+
+    let (channel, mut registry) = registry::<u32, Ping, mpsc::Sender<Ping>>();
+    let worker = std::thread::spawn(move || {
+        registry.run();
+    });
+
+    channel.subscribe(1, send1).expect("Success");
+    channel.subscribe_broadcast(2, send2).expect("Success");
+    channel.subscribe_broadcast(2, send3).expect("Success");
+    channel.send_to(1, Ping).expect("Success");
+    channel.broadcast_to(2, Ping).expect("Success");
+
+    drop(channel);
+    worker.join().expect("Finish successfully");
+
+    assert_eq!(recv1.iter().collect::<Vec<_>>(), [Ping]);
+    assert_eq!(recv2.iter().collect::<Vec<_>>(), [Ping]);
+    assert_eq!(recv3.iter().collect::<Vec<_>>(), [Ping]);
+    //Direct send never clones, while broadcast moves message into last subscriber
+    assert_eq!(CLONES.load(Ordering::Relaxed), 1);
+}